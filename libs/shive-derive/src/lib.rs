@@ -1,10 +1,13 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{self, Data, Fields, GenericArgument, PathArguments, Type};
+use quote::{format_ident, quote};
+use syn::{
+    self, Data, Field, Fields, GenericArgument, LitStr, PathArguments, Type,
+    punctuated::Punctuated, token::Comma,
+};
 
-#[proc_macro_derive(Service)]
+#[proc_macro_derive(Service, attributes(inject, dispose))]
 pub fn service_derive(input: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as a syntax tree
     // that we can manipulate
@@ -14,63 +17,124 @@ pub fn service_derive(input: TokenStream) -> TokenStream {
     impl_service_macro(&ast)
 }
 
+#[proc_macro_derive(AsyncService, attributes(inject, dispose))]
+pub fn async_service_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+
+    // Build the trait implementations, the services are awaited in the async init
+    impl_async_service_macro(&ast)
+}
+
 fn impl_service_macro(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    let fields = match named_fields(ast) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
 
-    let fields = match ast.data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => &fields.named,
-            _ => unimplemented!("Service macro can only be used with structs with named fields"),
-        },
-        _ => unimplemented!("Service macro can only be used with structs"),
+    let injections = match fields
+        .iter()
+        .map(parse_injection)
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(injections) => injections,
+        Err(error) => return error.to_compile_error().into(),
     };
 
-    let gen_fields = fields.iter().map(|field| {
+    let gen_fields = fields
+        .iter()
+        .zip(&injections)
+        .map(|(field, injection)| gen_field(field, injection));
+
+    let gen_dependencies = injections.iter().map(gen_dependency);
+
+    let gen_field_names = fields.iter().map(|field| {
         let field_name = &field.ident;
-        let field_type = &field.ty;
-
-        // Check if the type is an Arc and extract the inner type if it is
-        let inner_type = if let Type::Path(type_path) = field_type {
-            if type_path.path.segments.len() == 1 && type_path.path.segments[0].ident == "Arc" {
-                if let PathArguments::AngleBracketed(ref args) =
-                    type_path.path.segments[0].arguments
-                {
-                    if let Some(GenericArgument::Type(inner_ty)) = args.args.first() {
-                        Some(inner_ty)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
+        quote! {
+            #field_name,
+        }
+    });
+
+    let gen_disposable = gen_disposable(ast);
+
+    let gen_service = quote! {
+        impl Service for #name {
+            fn init(service_provider: &dyn shive::service::ServiceProvider) -> Arc<dyn shive::service::Service>
+            where
+                Self: Sized,
+            {
+                Self::try_init(service_provider).unwrap_or_else(|error| panic!("{}", error))
             }
-        } else {
-            None
-        };
 
-        match inner_type {
-            Some(it) => {
-                if let Type::TraitObject(_) = it {
-                    quote! {
-                        let #field_name = shive::service::get_trait_instance::<#inner_type>(service_provider)
-                            .expect("Cannot get trait type from service manager");
-                    }
-                }
-                else {
-                    quote! {
-                        let #field_name = shive::service::get_instance::<#inner_type>(service_provider)
-                            .expect("Cannot get type from service manager");
-                    }
-                }
+            fn try_init(
+                service_provider: &dyn shive::service::ServiceProvider,
+            ) -> Result<Arc<dyn shive::service::Service>, shive::error::Error>
+            where
+                Self: Sized,
+            {
+                #(#gen_fields)*
+
+                Ok(Arc::new(Self { #(#gen_field_names)* }))
+            }
+
+            fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+                self
             }
-            None => {
-                unimplemented!("Struct property type must be inside an Arc")
+
+            #gen_disposable
+
+            fn dependencies() -> Vec<shive::service_dependency::ServiceDependency>
+            where
+                Self: Sized,
+            {
+                vec![#(#gen_dependencies)*]
             }
         }
+    };
+
+    gen_service.into()
+}
+
+fn impl_async_service_macro(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let fields = match named_fields(ast) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    let injections = match fields
+        .iter()
+        .map(parse_injection)
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(injections) => injections,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    // Instances are awaited first, the other injections are resolved synchronously
+    let gen_async_fields = fields
+        .iter()
+        .zip(&injections)
+        .filter_map(|(field, injection)| gen_async_field(field, injection));
+    let gen_fields: Vec<_> = fields
+        .iter()
+        .zip(&injections)
+        .filter(|(_, injection)| {
+            !matches!(
+                injection,
+                Injection::Instance { .. } | Injection::TraitInstance { .. }
+            )
+        })
+        .map(|(field, injection)| gen_field(field, injection))
+        .collect();
+    let gen_service_provider = (!gen_fields.is_empty()).then(|| {
+        quote! {
+            let service_provider = shared_provider.as_service_provider();
+        }
     });
 
+    let gen_dependencies = injections.iter().map(gen_dependency);
+
     let gen_field_names = fields.iter().map(|field| {
         let field_name = &field.ident;
         quote! {
@@ -78,22 +142,482 @@ fn impl_service_macro(ast: &syn::DeriveInput) -> TokenStream {
         }
     });
 
+    let gen_disposable = gen_disposable(ast);
+
     let gen_service = quote! {
         impl Service for #name {
-            fn init(service_provider: &dyn shive::service::ServiceResolver) -> Arc<dyn shive::service::Service>
+            fn init(service_provider: &dyn shive::service::ServiceProvider) -> Arc<dyn shive::service::Service>
             where
                 Self: Sized,
             {
-                #(#gen_fields)*
+                <Self as shive::service::Service>::try_init(service_provider)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
 
-                Arc::new(Self { #(#gen_field_names)* })
+            // Asynchronous services are created by AsyncService::try_init
+            fn try_init(
+                _: &dyn shive::service::ServiceProvider,
+            ) -> Result<Arc<dyn shive::service::Service>, shive::error::Error>
+            where
+                Self: Sized,
+            {
+                Err(shive::error::Error::AsyncInitRequired {
+                    type_name: std::any::type_name::<Self>().to_string(),
+                })
             }
 
             fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
                 self
             }
+
+            #gen_disposable
+
+            fn dependencies() -> Vec<shive::service_dependency::ServiceDependency>
+            where
+                Self: Sized,
+            {
+                vec![#(#gen_dependencies)*]
+            }
+        }
+
+        impl shive::async_service::AsyncService for #name {
+            async fn init(
+                shared_provider: Arc<dyn shive::service::ServiceProvider + Send + Sync>,
+            ) -> Arc<dyn shive::service::Service>
+            where
+                Self: Sized,
+            {
+                <Self as shive::async_service::AsyncService>::try_init(shared_provider)
+                    .await
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+
+            async fn try_init(
+                shared_provider: Arc<dyn shive::service::ServiceProvider + Send + Sync>,
+            ) -> Result<Arc<dyn shive::service::Service>, shive::error::Error>
+            where
+                Self: Sized,
+            {
+                #(#gen_async_fields)*
+                #gen_service_provider
+                #(#gen_fields)*
+
+                Ok(Arc::new(Self { #(#gen_field_names)* }))
+            }
         }
     };
 
     gen_service.into()
 }
+
+/// Get the named fields of the struct deriving a service.
+fn named_fields(ast: &syn::DeriveInput) -> syn::Result<&Punctuated<Field, Comma>> {
+    match ast.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new_spanned(
+                ast,
+                "Service macro can only be used with structs with named fields",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            ast,
+            "Service macro can only be used with structs",
+        )),
+    }
+}
+
+/// Await the instance injected in a struct property of an asynchronous service.
+fn gen_async_field(field: &Field, injection: &Injection) -> Option<proc_macro2::TokenStream> {
+    let field_name = &field.ident;
+
+    match injection {
+        Injection::Instance { ty, key: Some(key) } => Some(quote! {
+            let #field_name = shive::async_service::get_keyed_instance_async::<#ty>(&*shared_provider, #key)
+                .await?;
+        }),
+        Injection::Instance { ty, key: None } => Some(quote! {
+            let #field_name = shive::async_service::get_instance_async::<#ty>(&*shared_provider)
+                .await?;
+        }),
+        Injection::TraitInstance { ty, key: Some(key) } => Some(quote! {
+            let #field_name = shive::async_service::get_keyed_trait_instance_async::<#ty>(&*shared_provider, #key)
+                .await?;
+        }),
+        Injection::TraitInstance { ty, key: None } => Some(quote! {
+            let #field_name = shive::async_service::get_trait_instance_async::<#ty>(&*shared_provider)
+                .await?;
+        }),
+        _ => None,
+    }
+}
+
+/// Resolve the service injected in a struct property.
+fn gen_field(field: &Field, injection: &Injection) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+
+    match injection {
+        Injection::Instance { ty, key: Some(key) } => quote! {
+            let #field_name = shive::service::get_keyed_instance::<#ty>(service_provider, #key)?;
+        },
+        Injection::Instance { ty, key: None } => quote! {
+            let #field_name = shive::service::get_instance::<#ty>(service_provider)?;
+        },
+        Injection::TraitInstance { ty, key: Some(key) } => quote! {
+            let #field_name = shive::service::get_keyed_trait_instance::<#ty>(service_provider, #key)?;
+        },
+        Injection::TraitInstance { ty, key: None } => quote! {
+            let #field_name = shive::service::get_trait_instance::<#ty>(service_provider)?;
+        },
+        Injection::TraitCollection { ty } => quote! {
+            let #field_name = shive::service::get_all_trait_instances::<#ty>(service_provider)?;
+        },
+        Injection::OptionalInstance { ty, key: Some(key) } => quote! {
+            let #field_name = shive::service::try_get_keyed_instance::<#ty>(service_provider, #key)?;
+        },
+        Injection::OptionalInstance { ty, key: None } => quote! {
+            let #field_name = shive::service::try_get_instance::<#ty>(service_provider)?;
+        },
+        Injection::OptionalTraitInstance { ty, key: Some(key) } => quote! {
+            let #field_name = shive::service::try_get_keyed_trait_instance::<#ty>(service_provider, #key)?;
+        },
+        Injection::OptionalTraitInstance { ty, key: None } => quote! {
+            let #field_name = shive::service::try_get_trait_instance::<#ty>(service_provider)?;
+        },
+        Injection::LazyInstance { ty, key: Some(key) } => quote! {
+            let #field_name = shive::lazy::Lazy::new(service_provider, |service_provider| {
+                shive::service::get_keyed_instance::<#ty>(service_provider, #key)
+            });
+        },
+        Injection::LazyInstance { ty, key: None } => quote! {
+            let #field_name = shive::lazy::get_lazy_instance::<#ty>(service_provider);
+        },
+        Injection::LazyTraitInstance { ty, key: Some(key) } => quote! {
+            let #field_name = shive::lazy::Lazy::new(service_provider, |service_provider| {
+                shive::service::get_keyed_trait_instance::<#ty>(service_provider, #key)
+            });
+        },
+        Injection::LazyTraitInstance { ty, key: None } => quote! {
+            let #field_name = shive::lazy::get_lazy_trait_instance::<#ty>(service_provider);
+        },
+        Injection::Factory { ty, key: Some(key) } => quote! {
+            let #field_name = shive::factory::get_keyed_factory::<#ty>(service_provider, #key);
+        },
+        Injection::Factory { ty, key: None } => quote! {
+            let #field_name = shive::factory::get_factory::<#ty>(service_provider);
+        },
+        Injection::TraitFactory { ty, key: Some(key) } => quote! {
+            let #field_name =
+                shive::factory::get_keyed_trait_factory::<#ty>(service_provider, #key);
+        },
+        Injection::TraitFactory { ty, key: None } => quote! {
+            let #field_name = shive::factory::get_trait_factory::<#ty>(service_provider);
+        },
+    }
+}
+
+/// Declare the dependency of an injected service.
+fn gen_dependency(injection: &Injection) -> proc_macro2::TokenStream {
+    match injection {
+        Injection::Instance { ty, key: Some(key) }
+        | Injection::TraitInstance { ty, key: Some(key) } => quote! {
+            shive::service_dependency::ServiceDependency::required_keyed::<#ty>(#key),
+        },
+        Injection::Instance { ty, key: None } => {
+            gen_generic_dependency(quote!(required), ty, quote!(GenericDependency))
+        }
+        Injection::TraitInstance { ty, key: None } => {
+            gen_generic_dependency(quote!(required), ty, quote!(GenericTraitDependency))
+        }
+        Injection::TraitCollection { ty } => quote! {
+            shive::service_dependency::ServiceDependency::collection::<#ty>(),
+        },
+        Injection::OptionalInstance { ty, key: Some(key) }
+        | Injection::OptionalTraitInstance { ty, key: Some(key) } => quote! {
+            shive::service_dependency::ServiceDependency::optional_keyed::<#ty>(#key),
+        },
+        Injection::OptionalInstance { ty, key: None } => {
+            gen_generic_dependency(quote!(optional), ty, quote!(GenericDependency))
+        }
+        Injection::OptionalTraitInstance { ty, key: None } => {
+            gen_generic_dependency(quote!(optional), ty, quote!(GenericTraitDependency))
+        }
+        Injection::LazyInstance { ty, key: Some(key) }
+        | Injection::LazyTraitInstance { ty, key: Some(key) } => quote! {
+            shive::service_dependency::ServiceDependency::lazy_keyed::<#ty>(#key),
+        },
+        Injection::LazyInstance { ty, key: None } => {
+            gen_generic_dependency(quote!(lazy), ty, quote!(GenericDependency))
+        }
+        Injection::LazyTraitInstance { ty, key: None } => {
+            gen_generic_dependency(quote!(lazy), ty, quote!(GenericTraitDependency))
+        }
+        Injection::Factory { ty, key: Some(key) }
+        | Injection::TraitFactory { ty, key: Some(key) } => quote! {
+            shive::service_dependency::ServiceDependency::factory_keyed::<#ty>(#key),
+        },
+        Injection::Factory { ty, key: None } => {
+            gen_generic_dependency(quote!(factory), ty, quote!(GenericDependency))
+        }
+        Injection::TraitFactory { ty, key: None } => {
+            gen_generic_dependency(quote!(factory), ty, quote!(GenericTraitDependency))
+        }
+    }
+}
+
+/// Declare an unkeyed dependency, with its instantiation if the type is an open generic one.
+fn gen_generic_dependency(
+    constructor: proc_macro2::TokenStream,
+    ty: &Type,
+    generic_trait: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        {
+            use shive::generic_service::{ClosedDependency as _, #generic_trait as _};
+
+            shive::service_dependency::ServiceDependency::#constructor::<#ty>().with_instantiation(
+                (&shive::generic_service::DependencyProbe::<#ty>::new()).instantiation(),
+            )
+        },
+    }
+}
+
+/// Expose the disposable view of a service marked with `#[dispose]`.
+fn gen_disposable(ast: &syn::DeriveInput) -> Option<proc_macro2::TokenStream> {
+    ast.attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("dispose"))
+        .then(|| {
+            quote! {
+                fn as_disposable(self: Arc<Self>) -> Option<Arc<dyn shive::dispose::Dispose>> {
+                    Some(self)
+                }
+            }
+        })
+}
+
+#[proc_macro_attribute]
+pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut output: proc_macro2::TokenStream = item.clone().into();
+    let ast: syn::DeriveInput = match syn::parse(item) {
+        Ok(ast) => ast,
+        Err(error) => {
+            output.extend(error.to_compile_error());
+            return output.into();
+        }
+    };
+
+    // Parse the registration of the service
+    let mut registration = Registration::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("lifetime") {
+            registration.lifetime = Some(meta.value()?.parse::<LitStr>()?);
+        } else if meta.path.is_ident("provides") {
+            registration.provides = Some(meta.value()?.parse::<LitStr>()?.parse::<Type>()?);
+        } else if meta.path.is_ident("key") {
+            registration.key = Some(meta.value()?.parse::<LitStr>()?);
+        } else {
+            return Err(meta.error("unsupported service attribute"));
+        }
+
+        Ok(())
+    });
+    let registration = syn::parse::Parser::parse(parser, attr).map(|_| registration);
+    match registration.and_then(|registration| impl_discovery_macro(&ast, &registration)) {
+        Ok(discovery) => output.extend(discovery),
+        Err(error) => output.extend(error.to_compile_error()),
+    }
+
+    output.into()
+}
+
+/// Registration declared by the `#[service(...)]` attribute.
+#[derive(Default)]
+struct Registration {
+    /// `singleton` by default, `scoped` or `transient`.
+    lifetime: Option<LitStr>,
+    /// Trait provided by the service.
+    provides: Option<Type>,
+    /// Key of the registration.
+    key: Option<LitStr>,
+}
+
+fn impl_discovery_macro(
+    ast: &syn::DeriveInput,
+    registration: &Registration,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+
+    if !ast.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.generics,
+            "Service attribute cannot be used with generic types",
+        ));
+    }
+
+    let lifetime = registration
+        .lifetime
+        .as_ref()
+        .map(LitStr::value)
+        .unwrap_or_else(|| "singleton".to_string());
+    if !matches!(lifetime.as_str(), "singleton" | "scoped" | "transient") {
+        return Err(syn::Error::new_spanned(
+            &registration.lifetime,
+            "Service lifetime must be singleton, scoped or transient",
+        ));
+    }
+
+    let method = format_ident!(
+        "add_{}{}{}",
+        if registration.key.is_some() {
+            "keyed_"
+        } else {
+            ""
+        },
+        if registration.provides.is_some() {
+            "trait_"
+        } else {
+            ""
+        },
+        lifetime
+    );
+    let key = registration.key.iter();
+
+    let gen_register = match &registration.provides {
+        Some(provides) => quote! {
+            service_container.#method::<#provides, #name>(
+                #(#key,)*
+                shive::service::ServiceResolver::<#provides> {
+                    as_interface: |service| service.downcast::<#name>().unwrap(),
+                },
+            )
+        },
+        None => quote! {
+            service_container.#method::<#name>(#(#key)*)
+        },
+    };
+
+    let static_name = format_ident!("__SHIVE_SERVICE_{}", name.to_string().to_uppercase());
+
+    Ok(quote! {
+        #[shive::discovery::linkme::distributed_slice(shive::discovery::DISCOVERED_SERVICES)]
+        #[linkme(crate = shive::discovery::linkme)]
+        static #static_name: shive::discovery::DiscoveredService =
+            shive::discovery::DiscoveredService {
+                type_name: concat!(module_path!(), "::", stringify!(#name)),
+                register: |service_container| #gen_register,
+            };
+    })
+}
+
+/// Service injected in a struct property.
+enum Injection<'a> {
+    /// `Arc<T>` property.
+    Instance { ty: &'a Type, key: Option<LitStr> },
+    /// `Arc<dyn Trait>` property.
+    TraitInstance { ty: &'a Type, key: Option<LitStr> },
+    /// `Vec<Arc<dyn Trait>>` property receiving all the implementations of the trait.
+    TraitCollection { ty: &'a Type },
+    /// `Option<Arc<T>>` property, `None` if the type is not registered.
+    OptionalInstance { ty: &'a Type, key: Option<LitStr> },
+    /// `Option<Arc<dyn Trait>>` property, `None` if the trait is not registered.
+    OptionalTraitInstance { ty: &'a Type, key: Option<LitStr> },
+    /// `Lazy<T>` property created on first use.
+    LazyInstance { ty: &'a Type, key: Option<LitStr> },
+    /// `Lazy<dyn Trait>` property created on first use.
+    LazyTraitInstance { ty: &'a Type, key: Option<LitStr> },
+    /// `Factory<T>` property creating new instances.
+    Factory { ty: &'a Type, key: Option<LitStr> },
+    /// `Factory<dyn Trait>` property creating new instances.
+    TraitFactory { ty: &'a Type, key: Option<LitStr> },
+}
+
+/// Get the injection of a struct property from its type and attributes.
+fn parse_injection(field: &Field) -> syn::Result<Injection<'_>> {
+    let key = inject_key(field)?;
+
+    if let Some(item_type) = wrapped_type(&field.ty, "Vec") {
+        if let Some(key) = key {
+            return Err(syn::Error::new_spanned(
+                key,
+                "Keyed services cannot be injected in a Vec",
+            ));
+        }
+
+        return match wrapped_type(item_type, "Arc") {
+            Some(ty @ Type::TraitObject(_)) => Ok(Injection::TraitCollection { ty }),
+            _ => Err(syn::Error::new_spanned(
+                &field.ty,
+                "Struct property Vec must contain Arc of a trait",
+            )),
+        };
+    }
+
+    if let Some(item_type) = wrapped_type(&field.ty, "Option") {
+        return match wrapped_type(item_type, "Arc") {
+            Some(ty @ Type::TraitObject(_)) => Ok(Injection::OptionalTraitInstance { ty, key }),
+            Some(ty) => Ok(Injection::OptionalInstance { ty, key }),
+            None => Err(syn::Error::new_spanned(
+                &field.ty,
+                "Struct property Option must contain an Arc",
+            )),
+        };
+    }
+
+    match wrapped_type(&field.ty, "Lazy") {
+        Some(ty @ Type::TraitObject(_)) => return Ok(Injection::LazyTraitInstance { ty, key }),
+        Some(ty) => return Ok(Injection::LazyInstance { ty, key }),
+        None => {}
+    }
+
+    match wrapped_type(&field.ty, "Factory") {
+        Some(ty @ Type::TraitObject(_)) => return Ok(Injection::TraitFactory { ty, key }),
+        Some(ty) => return Ok(Injection::Factory { ty, key }),
+        None => {}
+    }
+
+    match wrapped_type(&field.ty, "Arc") {
+        Some(ty @ Type::TraitObject(_)) => Ok(Injection::TraitInstance { ty, key }),
+        Some(ty) => Ok(Injection::Instance { ty, key }),
+        None => Err(syn::Error::new_spanned(
+            &field.ty,
+            "Struct property type must be inside an Arc, a Lazy or a Factory",
+        )),
+    }
+}
+
+/// Extract the type argument of a `Wrapper<T>` type.
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    if let Type::Path(type_path) = ty
+        && type_path.path.segments.len() == 1
+        && type_path.path.segments[0].ident == wrapper
+        && let PathArguments::AngleBracketed(ref args) = type_path.path.segments[0].arguments
+        && let Some(GenericArgument::Type(inner_ty)) = args.args.first()
+    {
+        Some(inner_ty)
+    } else {
+        None
+    }
+}
+
+/// Get the key of the `#[inject(key = "...")]` attribute of the field.
+fn inject_key(field: &Field) -> syn::Result<Option<LitStr>> {
+    let mut key = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("inject"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                key = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported inject attribute"))
+            }
+        })?;
+    }
+
+    Ok(key)
+}
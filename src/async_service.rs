@@ -0,0 +1,173 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use tokio::sync::OnceCell;
+
+use crate::{
+    error::Error,
+    service::{Service, ServiceProvider, ServiceResolver},
+    service_definition::{AsyncServiceInit, ServiceDefinition},
+    service_key::ServiceKey,
+};
+
+/// Instance of a service being created asynchronously.
+pub type AsyncInstance = Pin<Box<dyn Future<Output = Result<Arc<dyn Service>, Error>> + Send>>;
+
+/// Initialization of an asynchronous singleton or scoped instance, awaited by the concurrent calls.
+pub(crate) type AsyncInstanceCell = Arc<OnceCell<Arc<dyn Service>>>;
+
+/// Service initialized asynchronously, resolved with `get_instance_async`.
+pub trait AsyncService: Service {
+    /// Create the service from an owned handle on the service provider.
+    fn init(
+        service_provider: Arc<dyn ServiceProvider + Send + Sync>,
+    ) -> impl Future<Output = Arc<dyn Service>> + Send
+    where
+        Self: Sized;
+
+    /// Create the service, failing instead of panicking when a dependency cannot be resolved.
+    /// The service providers create the services with this function.
+    fn try_init(
+        service_provider: Arc<dyn ServiceProvider + Send + Sync>,
+    ) -> impl Future<Output = Result<Arc<dyn Service>, Error>> + Send
+    where
+        Self: Sized,
+    {
+        async move { Ok(<Self as AsyncService>::init(service_provider).await) }
+    }
+}
+
+/// Get an instance of the specified type, awaiting its asynchronous initialization.
+/// Initialize new object depending on the lifetime.
+pub fn get_instance_async<T: Service>(
+    service_provider: &dyn ServiceProvider,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    resolve_instance_async::<T>(service_provider, ServiceKey::of::<T>())
+}
+
+/// Get an instance of the specified type registered under the key, awaiting its asynchronous initialization.
+/// Initialize new object depending on the lifetime.
+pub fn get_keyed_instance_async<T: Service>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    resolve_instance_async::<T>(service_provider, ServiceKey::named::<T>(key))
+}
+
+/// Get an instance of the specified trait, awaiting its asynchronous initialization.
+/// Initialize new object depending on the lifetime.
+pub fn get_trait_instance_async<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    resolve_trait_instance_async::<T>(service_provider, ServiceKey::of::<T>())
+}
+
+/// Get an instance of the specified trait registered under the key, awaiting its asynchronous initialization.
+/// Initialize new object depending on the lifetime.
+pub fn get_keyed_trait_instance_async<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    resolve_trait_instance_async::<T>(service_provider, ServiceKey::named::<T>(key))
+}
+
+/// Get the instances of all the implementations of the specified trait in registration order,
+/// awaiting their asynchronous initialization.
+pub fn get_all_trait_instances_async<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> impl Future<Output = Result<Vec<Arc<T>>, Error>> + Send + 'static {
+    let instances: Vec<_> = service_provider
+        .get_service_definitions(&ServiceKey::of::<T>())
+        .iter()
+        .map(|service_definition| {
+            resolve_trait_definition_async::<T>(service_provider, service_definition)
+        })
+        .collect();
+
+    async move {
+        let mut services = Vec::with_capacity(instances.len());
+        for instance in instances {
+            services.push(instance.await?);
+        }
+
+        Ok(services)
+    }
+}
+
+/// Wrap the asynchronous init function of a service.
+pub(crate) fn async_init<T: AsyncService>() -> AsyncServiceInit {
+    Arc::new(|service_provider| Box::pin(<T as AsyncService>::try_init(service_provider)))
+}
+
+/// Start the creation of the instance registered under the service key.
+fn create_instance_async(
+    service_provider: &dyn ServiceProvider,
+    key: &ServiceKey,
+) -> Result<AsyncInstance, Error> {
+    match service_provider.get_service_definition(key) {
+        Some(service_definition) => {
+            Ok(service_provider.get_or_create_instance_from_definition_async(service_definition))
+        }
+        None => Err(Error::NotRegistered {
+            type_name: key.to_string(),
+        }),
+    }
+}
+
+/// Get or create the instance of the type registered under the service key.
+fn resolve_instance_async<T: Service>(
+    service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    let instance = create_instance_async(service_provider, &key);
+
+    async move {
+        let service = instance?.await?;
+
+        Arc::downcast::<T>(service.as_any()).map_err(|_| Error::DowncastFailed {
+            type_name: key.to_string(),
+        })
+    }
+}
+
+/// Get or create the instance of the trait registered under the service key.
+fn resolve_trait_instance_async<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    let instance = match service_provider.get_service_definition(&key) {
+        Some(service_definition) => Ok(resolve_trait_definition_async::<T>(
+            service_provider,
+            service_definition,
+        )),
+        None => Err(Error::NotRegistered {
+            type_name: key.to_string(),
+        }),
+    };
+
+    async move { instance?.await }
+}
+
+/// Get or create the instance of a trait registration.
+fn resolve_trait_definition_async<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    service_definition: &ServiceDefinition,
+) -> impl Future<Output = Result<Arc<T>, Error>> + Send + 'static {
+    // Get service resolver
+    let as_interface = service_definition
+        .resolver
+        .as_ref()
+        .and_then(|resolver| resolver.downcast_ref::<ServiceResolver<T>>())
+        .map(|service_resolver| service_resolver.as_interface)
+        .ok_or_else(|| Error::DowncastFailed {
+            type_name: std::any::type_name::<T>().to_string(),
+        });
+    let instance =
+        service_provider.get_or_create_instance_from_definition_async(service_definition);
+
+    async move {
+        let as_interface = as_interface?;
+        let service = instance.await?;
+
+        Ok(as_interface(service.as_any()))
+    }
+}
@@ -0,0 +1,69 @@
+use std::{collections::HashSet, sync::Arc};
+
+/// Condition of a registration, evaluated when the service provider is built.
+#[derive(Clone)]
+pub enum Condition {
+    /// The profile is active in the service container.
+    Profile(String),
+    /// The environment variable is set, with the value if specified.
+    Env { name: String, value: Option<String> },
+    /// The predicate returns `true`.
+    Predicate(Arc<dyn Fn() -> bool + Send + Sync>),
+    /// All the conditions are met.
+    All(Vec<Condition>),
+}
+
+impl Condition {
+    /// Condition met when the profile is active.
+    pub fn profile(profile: &str) -> Self {
+        Self::Profile(profile.to_string())
+    }
+
+    /// Condition met when the environment variable is set.
+    pub fn env(name: &str) -> Self {
+        Self::Env {
+            name: name.to_string(),
+            value: None,
+        }
+    }
+
+    /// Condition met when the environment variable has the value.
+    pub fn env_eq(name: &str, value: &str) -> Self {
+        Self::Env {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        }
+    }
+
+    /// Condition met when the predicate returns `true`.
+    pub fn predicate<F: Fn() -> bool + Send + Sync + 'static>(predicate: F) -> Self {
+        Self::Predicate(Arc::new(predicate))
+    }
+
+    /// Condition met when this condition and the other one are met.
+    pub fn and(self, other: Condition) -> Self {
+        match self {
+            Self::All(mut conditions) => {
+                conditions.push(other);
+                Self::All(conditions)
+            }
+            condition => Self::All(vec![condition, other]),
+        }
+    }
+
+    /// Evaluate the condition with the active profiles.
+    pub(crate) fn is_met(&self, profiles: &HashSet<String>) -> bool {
+        match self {
+            Self::Profile(profile) => profiles.contains(profile),
+            Self::Env { name, value } => match (std::env::var(name), value) {
+                (Ok(actual), Some(expected)) => actual == *expected,
+                (Ok(_), None) => true,
+                (Err(_), _) => false,
+            },
+            Self::Predicate(predicate) => predicate(),
+            Self::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.is_met(profiles)),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{any::type_name, collections::BTreeMap, fmt::Display, path::Path};
+
+use serde::{
+    Deserializer,
+    de::{
+        DeserializeOwned, IntoDeserializer, Visitor, value::Error as ValueError,
+        value::MapDeserializer,
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::error::Error;
+
+/// Bind a configuration from a section of a JSON document.
+/// The section path is separated by dots, an empty section binds the whole document.
+pub fn from_json<T: DeserializeOwned>(json: &str, section: &str) -> Result<T, Error> {
+    let document: serde_json::Value =
+        serde_json::from_str(json).map_err(|error| bind_error::<T>(error))?;
+
+    bind_section(document, section)
+}
+
+/// Bind a configuration from a section of a TOML document.
+/// The section path is separated by dots, an empty section binds the whole document.
+pub fn from_toml<T: DeserializeOwned>(toml: &str, section: &str) -> Result<T, Error> {
+    let document: toml::Table = toml::from_str(toml).map_err(|error| bind_error::<T>(error))?;
+    let document = serde_json::to_value(document).map_err(|error| bind_error::<T>(error))?;
+
+    bind_section(document, section)
+}
+
+/// Bind a configuration from a section of a JSON or TOML file, depending on its extension.
+pub fn from_file<T: DeserializeOwned>(path: impl AsRef<Path>, section: &str) -> Result<T, Error> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).map_err(|error| {
+        Error::Configuration(format!(
+            "cannot read configuration file {}: {}",
+            path.display(),
+            error
+        ))
+    })?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => from_json(&content, section),
+        Some("toml") => from_toml(&content, section),
+        _ => Err(Error::Configuration(format!(
+            "unsupported configuration file {}",
+            path.display()
+        ))),
+    }
+}
+
+/// Bind a configuration from the environment variables starting with the prefix.
+/// `PREFIX_NAME` binds the field `name`, `__` separates the nested sections.
+/// The variables with a name which is not valid unicode are skipped.
+pub fn from_env<T: DeserializeOwned>(prefix: &str) -> Result<T, Error> {
+    let prefix = format!("{}_", prefix);
+    let mut root = BTreeMap::new();
+
+    for (name, value) in std::env::vars_os() {
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+
+        if let Some(path) = name.strip_prefix(&prefix) {
+            let value = value.into_string().map_err(|_| {
+                Error::Configuration(format!(
+                    "environment variable {} is not valid unicode",
+                    name
+                ))
+            })?;
+            let path: Vec<String> = path.to_lowercase().split("__").map(String::from).collect();
+            insert_env_value(&mut root, &path, value);
+        }
+    }
+
+    T::deserialize(EnvNode::Section(root)).map_err(|error| bind_error::<T>(error))
+}
+
+/// Get the section of the document and bind the configuration.
+fn bind_section<T: DeserializeOwned>(
+    document: serde_json::Value,
+    section: &str,
+) -> Result<T, Error> {
+    let mut value = document;
+
+    for name in section.split('.').filter(|name| !name.is_empty()) {
+        value = match value {
+            serde_json::Value::Object(mut object) => object.remove(name),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            Error::Configuration(format!("configuration section {} not found", section))
+        })?;
+    }
+
+    serde_json::from_value(value).map_err(|error| bind_error::<T>(error))
+}
+
+/// Error of a configuration which cannot be bound.
+fn bind_error<T>(error: impl Display) -> Error {
+    Error::Configuration(format!("cannot bind {}: {}", type_name::<T>(), error))
+}
+
+/// Insert the value of an environment variable in its section.
+fn insert_env_value(section: &mut BTreeMap<String, EnvNode>, path: &[String], value: String) {
+    match path {
+        [] => {}
+        [name] => {
+            section.insert(name.clone(), EnvNode::Value(value));
+        }
+        [name, path @ ..] => {
+            let node = section
+                .entry(name.clone())
+                .or_insert_with(|| EnvNode::Section(BTreeMap::new()));
+
+            if let EnvNode::Section(section) = node {
+                insert_env_value(section, path, value);
+            }
+        }
+    }
+}
+
+/// Environment variable or section of environment variables.
+/// Values are parsed when a number or a boolean is expected.
+enum EnvNode {
+    Value(String),
+    Section(BTreeMap<String, EnvNode>),
+}
+
+impl<'de> IntoDeserializer<'de, ValueError> for EnvNode {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserialize a value parsed from the environment variable.
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+                match self {
+                    EnvNode::Value(value) => {
+                        visitor.$visit(value.parse().map_err(serde::de::Error::custom)?)
+                    }
+                    section => section.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for EnvNode {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        match self {
+            EnvNode::Value(value) => visitor.visit_string(value),
+            EnvNode::Section(section) => {
+                visitor.visit_map(MapDeserializer::new(section.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        match self {
+            EnvNode::Value(value) => visitor.visit_enum(value.into_deserializer()),
+            section => section.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
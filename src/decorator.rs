@@ -0,0 +1,147 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    dispose::Dispose,
+    service::{Service, ServiceProvider, ServiceResolver},
+    service_definition::{AsyncServiceInit, ServiceDefinition, ServiceInit},
+    service_dependency::ServiceDependency,
+    service_lifetime::ServiceLifetime,
+};
+
+/// Decorator wrapping the previous registration of a trait service.
+pub trait Decorator<I: ?Sized + Send + Sync + 'static>: 'static {
+    /// Wrap the decorated instance.
+    fn decorate(inner: Arc<I>, service_provider: &dyn ServiceProvider) -> Arc<I>;
+
+    /// Services resolved by `decorate`, used to validate the service container.
+    fn dependencies() -> Vec<ServiceDependency> {
+        Vec::new()
+    }
+}
+
+/// Service holding the instance returned by a decorator,
+/// with the disposable view of the decorated instance.
+struct DecoratedService<I: ?Sized + Send + Sync + 'static> {
+    instance: Arc<I>,
+    inner: Option<Arc<dyn Dispose>>,
+}
+
+impl<I: ?Sized + Send + Sync + 'static> Service for DecoratedService<I> {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        unreachable!("Decorated services are created by their decorator")
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn as_disposable(self: Arc<Self>) -> Option<Arc<dyn Dispose>> {
+        self.inner.clone()
+    }
+}
+
+/// Resolve the decorated instance of a trait.
+fn as_decorated_interface<I: ?Sized + Send + Sync + 'static>(
+    service: Arc<dyn Any + Send + Sync + 'static>,
+) -> Arc<I> {
+    service
+        .downcast::<DecoratedService<I>>()
+        .expect("Cannot downcast decorated service")
+        .instance
+        .clone()
+}
+
+/// Wrap the service definition of a trait with the decorator.
+/// Unmanaged instances are decorated once, like singletons,
+/// and asynchronous services are decorated once their initialization completes.
+pub(crate) fn decorate_definition<I, D>(
+    service_definition: &mut ServiceDefinition,
+    unmanaged_instance: Option<Arc<dyn Service>>,
+) where
+    I: ?Sized + Send + Sync + 'static,
+    D: Decorator<I>,
+{
+    // Unmanaged instances are not disposed by the service provider
+    let is_unmanaged = unmanaged_instance.is_some();
+    let inner_init: ServiceInit = match unmanaged_instance {
+        Some(instance) => {
+            // The unmanaged instance is not created by the service provider
+            service_definition.dependencies.clear();
+            Arc::new(move |_| Ok(instance.clone()))
+        }
+        None => service_definition.init.clone(),
+    };
+    let inner_resolver = service_definition.resolver.clone();
+
+    service_definition.init = {
+        let inner_resolver = inner_resolver.clone();
+
+        Arc::new(move |service_provider| {
+            let inner_service = inner_init(service_provider)?;
+
+            Ok(decorate_service::<I, D>(
+                inner_service,
+                &inner_resolver,
+                is_unmanaged,
+                service_provider,
+            ))
+        })
+    };
+    if let Some(inner_async_init) = service_definition.async_init.clone() {
+        let async_init: AsyncServiceInit = Arc::new(move |service_provider| {
+            let inner_async_init = inner_async_init.clone();
+            let inner_resolver = inner_resolver.clone();
+
+            Box::pin(async move {
+                let inner_service = inner_async_init(service_provider.clone()).await?;
+
+                Ok(decorate_service::<I, D>(
+                    inner_service,
+                    &inner_resolver,
+                    is_unmanaged,
+                    service_provider.as_service_provider(),
+                ))
+            })
+        });
+        service_definition.async_init = Some(async_init);
+    }
+    service_definition.resolver = Some(Arc::new(ServiceResolver::<I> {
+        as_interface: as_decorated_interface::<I>,
+    }));
+    service_definition.dependencies.extend(D::dependencies());
+
+    if matches!(service_definition.lifetime, ServiceLifetime::Unmanaged) {
+        service_definition.lifetime = ServiceLifetime::Singleton;
+    }
+}
+
+/// Decorate the instance created by the previous registration of the trait.
+fn decorate_service<I, D>(
+    inner_service: Arc<dyn Service>,
+    inner_resolver: &Option<Arc<dyn Any + Send + Sync + 'static>>,
+    is_unmanaged: bool,
+    service_provider: &dyn ServiceProvider,
+) -> Arc<dyn Service>
+where
+    I: ?Sized + Send + Sync + 'static,
+    D: Decorator<I>,
+{
+    let service_resolver = inner_resolver
+        .as_ref()
+        .and_then(|resolver| resolver.downcast_ref::<ServiceResolver<I>>())
+        .expect("Cannot get service resolver");
+    let inner = if is_unmanaged {
+        None
+    } else {
+        inner_service.clone().as_disposable()
+    };
+    let instance = (service_resolver.as_interface)(inner_service.as_any());
+
+    Arc::new(DecoratedService {
+        instance: D::decorate(instance, service_provider),
+        inner,
+    })
+}
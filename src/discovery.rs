@@ -0,0 +1,16 @@
+use crate::service_container::ServiceContainer;
+
+#[doc(hidden)]
+pub use linkme;
+
+/// Service registered by the `#[shive::service]` attribute.
+pub struct DiscoveredService {
+    /// Path of the service type.
+    pub type_name: &'static str,
+    /// Declare the service in the service container.
+    pub register: fn(&mut ServiceContainer),
+}
+
+/// Services declared with the `#[shive::service]` attribute in all the crates linked into the binary.
+#[linkme::distributed_slice]
+pub static DISCOVERED_SERVICES: [DiscoveredService];
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+
+use crate::service::Service;
+
+/// Teardown of a service, called by the provider owning its instance.
+pub trait Dispose: Send + Sync {
+    /// Release the resources of the service.
+    fn dispose(&self);
+}
+
+/// Disposable instances in creation order, disposed in reverse order when dropped.
+#[derive(Default)]
+pub(crate) struct Disposables {
+    services: Mutex<Vec<Arc<dyn Dispose>>>,
+}
+
+impl Disposables {
+    /// Track the instance if it is disposable.
+    pub(crate) fn push(&self, service: &Arc<dyn Service>) {
+        if let Some(disposable) = service.clone().as_disposable() {
+            self.services.lock().unwrap().push(disposable);
+        }
+    }
+
+    /// Dispose the tracked instances in reverse creation order.
+    pub(crate) fn dispose(&self) {
+        let services = std::mem::take(&mut *self.services.lock().unwrap());
+
+        for service in services.into_iter().rev() {
+            service.dispose();
+        }
+    }
+}
+
+/// Teardown of a provider, run when the last clone owning the provider is dropped.
+/// The handles resolved by the services do not own the provider,
+/// so the instances holding one are released with the provider.
+pub(crate) struct Owner(Box<dyn Fn() + Send + Sync>);
+
+impl Owner {
+    pub(crate) fn new(teardown: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Box::new(teardown))
+    }
+}
+
+impl Drop for Owner {
+    fn drop(&mut self) {
+        (self.0)();
+    }
+}
+
+impl Drop for Disposables {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
@@ -1,6 +1,105 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::{service_lifetime::ServiceLifetime, validation::ValidationError};
+
+/// Source error of a failed service, shared by the clones of the error.
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
 #[derive(Debug, Clone)]
 pub enum Error {
-    Internal(String),
-    NotFound(String),
-    BadRequest(String),
+    /// No registration is declared for the type or trait.
+    NotRegistered { type_name: String },
+    /// The provider cannot create a service with this lifetime,
+    /// e.g. a scoped service requested from the root provider.
+    LifetimeMismatch {
+        requested: String,
+        lifetime: ServiceLifetime,
+    },
+    /// The instance cannot be converted to the requested type or trait.
+    DowncastFailed { type_name: String },
+    /// A service depends on itself, the path lists the implementations being created.
+    Cycle { path: Vec<String> },
+    /// The init function or factory of the service failed.
+    FactoryFailed {
+        type_name: String,
+        source: ErrorSource,
+    },
+    /// The service is initialized asynchronously and must be resolved with `get_instance_async`.
+    AsyncInitRequired { type_name: String },
+    /// A dependency failed while the services of the path were being created,
+    /// the last service of the path is the one which failed.
+    Resolution {
+        path: Vec<String>,
+        source: Box<Error>,
+    },
+    /// Problems found by the validation of the service container.
+    Validation(Vec<ValidationError>),
+    /// The configuration cannot be read or bound.
+    Configuration(String),
+    /// A hosted service failed to start or stop.
+    HostedServiceFailed { source: ErrorSource },
+    /// The hosted services did not stop within the shutdown timeout.
+    ShutdownTimeout { timeout: Duration },
+}
+
+impl Error {
+    /// Get the error at the origin of the resolution failure.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Resolution { source, .. } => source.root_cause(),
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotRegistered { type_name } => write!(f, "{} is not registered", type_name),
+            Error::LifetimeMismatch {
+                requested,
+                lifetime,
+            } => write!(
+                f,
+                "{:?} service {} cannot be created by this provider",
+                lifetime, requested
+            ),
+            Error::DowncastFailed { type_name } => {
+                write!(f, "cannot downcast the instance to {}", type_name)
+            }
+            Error::Cycle { path } => write!(f, "circular dependency {}", path.join(" -> ")),
+            Error::FactoryFailed { type_name, .. } => write!(f, "cannot create {}", type_name),
+            Error::AsyncInitRequired { type_name } => write!(
+                f,
+                "asynchronous service {} must be resolved with get_instance_async",
+                type_name
+            ),
+            Error::Resolution { path, .. } => {
+                write!(f, "cannot resolve {}", path.join(" -> "))
+            }
+            Error::Validation(validation_errors) => {
+                let messages: Vec<String> =
+                    validation_errors.iter().map(ToString::to_string).collect();
+                write!(f, "invalid service container: {}", messages.join(", "))
+            }
+            Error::Configuration(message) => write!(f, "invalid configuration: {}", message),
+            Error::HostedServiceFailed { .. } => write!(f, "hosted service failed"),
+            Error::ShutdownTimeout { timeout } => {
+                write!(f, "hosted services did not stop within {:?}", timeout)
+            }
+        }
+    }
+}
+
+/// The sources are not part of the messages, they are reported through `source`.
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::FactoryFailed { source, .. } | Error::HostedServiceFailed { source } => {
+                Some(source.as_ref())
+            }
+            Error::Resolution { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use crate::{
+    error::Error,
+    resolution_chain,
+    service::{Service, ServiceProvider, ServiceResolver},
+    service_key::ServiceKey,
+    service_lifetime::ServiceLifetime,
+};
+
+/// Function creating the instances of a factory.
+type FactoryCreate<T> = Box<dyn Fn(&dyn ServiceProvider) -> Result<Arc<T>, Error> + Send + Sync>;
+
+/// Factory creating a new instance of a registration on each call,
+/// with the dependencies resolved by the service provider it was resolved from.
+/// The factory does not keep the provider alive.
+pub struct Factory<T: ?Sized + Send + Sync + 'static> {
+    service_provider: Arc<dyn ServiceProvider + Send + Sync>,
+    create: FactoryCreate<T>,
+}
+
+impl<T: ?Sized + Send + Sync + 'static> Factory<T> {
+    /// Create a factory calling the function.
+    pub fn new<F>(service_provider: &dyn ServiceProvider, create: F) -> Self
+    where
+        F: Fn(&dyn ServiceProvider) -> Result<Arc<T>, Error> + Send + Sync + 'static,
+    {
+        Self {
+            service_provider: service_provider.clone_provider(),
+            create: Box::new(create),
+        }
+    }
+
+    /// Create a new instance.
+    pub fn create(&self) -> Result<Arc<T>, Error> {
+        (self.create)(self.service_provider.as_service_provider())
+    }
+}
+
+/// Get a factory of the specified type.
+pub fn get_factory<T: Service + 'static>(service_provider: &dyn ServiceProvider) -> Factory<T> {
+    Factory::new(service_provider, |service_provider| {
+        create_instance::<T>(service_provider, ServiceKey::of::<T>())
+    })
+}
+
+/// Get a factory of the specified type registered under the key.
+pub fn get_keyed_factory<T: Service + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> Factory<T> {
+    let key = ServiceKey::named::<T>(key);
+
+    Factory::new(service_provider, move |service_provider| {
+        create_instance::<T>(service_provider, key.clone())
+    })
+}
+
+/// Get a factory of the specified trait.
+pub fn get_trait_factory<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> Factory<T> {
+    Factory::new(service_provider, |service_provider| {
+        create_trait_instance::<T>(service_provider, ServiceKey::of::<T>())
+    })
+}
+
+/// Get a factory of the specified trait registered under the key.
+pub fn get_keyed_trait_factory<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> Factory<T> {
+    let key = ServiceKey::named::<T>(key);
+
+    Factory::new(service_provider, move |service_provider| {
+        create_trait_instance::<T>(service_provider, key.clone())
+    })
+}
+
+/// Create a new instance of the type registered under the service key.
+fn create_instance<T: Service + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
+) -> Result<Arc<T>, Error> {
+    let service = create_service(service_provider, &key)?;
+
+    Arc::downcast::<T>(service.as_any()).map_err(|_| Error::DowncastFailed {
+        type_name: key.to_string(),
+    })
+}
+
+/// Create a new instance of the trait registered under the service key.
+fn create_trait_instance<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
+) -> Result<Arc<T>, Error> {
+    let service_resolver = service_provider
+        .get_service_definition(&key)
+        .ok_or_else(|| Error::NotRegistered {
+            type_name: key.to_string(),
+        })?
+        .resolver
+        .clone()
+        .and_then(|resolver| resolver.downcast::<ServiceResolver<T>>().ok())
+        .ok_or_else(|| Error::DowncastFailed {
+            type_name: std::any::type_name::<T>().to_string(),
+        })?;
+    let service = create_service(service_provider, &key)?;
+
+    Ok((service_resolver.as_interface)(service.as_any()))
+}
+
+/// Create a new instance of the last registration of the key, disposed with the provider.
+fn create_service(
+    service_provider: &dyn ServiceProvider,
+    key: &ServiceKey,
+) -> Result<Arc<dyn Service>, Error> {
+    let service_definition = service_provider
+        .get_service_definition(key)
+        .ok_or_else(|| Error::NotRegistered {
+            type_name: key.to_string(),
+        })?;
+
+    if service_definition.lifetime == ServiceLifetime::Unmanaged {
+        return Err(Error::LifetimeMismatch {
+            requested: key.to_string(),
+            lifetime: ServiceLifetime::Unmanaged,
+        });
+    }
+
+    if service_definition.async_init.is_some() {
+        return Err(Error::AsyncInitRequired {
+            type_name: key.to_string(),
+        });
+    }
+
+    let _resolution_guard = resolution_chain::enter(service_definition)?;
+
+    service_provider.create_instance_from_definition(service_definition)
+}
@@ -0,0 +1,188 @@
+use std::{any::Any, fmt, marker::PhantomData, sync::Arc};
+
+use crate::{
+    error::Error,
+    service::{Service, ServiceProvider, ServiceResolver, resolve_trait_definition},
+    service_definition::ServiceInit,
+    service_dependency::ServiceDependency,
+    service_key::ServiceKey,
+};
+
+/// Closed instantiation of an open generic service.
+///
+/// All the instantiations share the same `Definition` type, which is declared once
+/// in the service container with `add_open_singleton`, `add_open_scoped` or `add_open_transient`.
+pub trait GenericService: Service {
+    type Definition: ?Sized + 'static;
+}
+
+/// Closed instantiation of an open generic trait, implemented on the trait object.
+pub trait GenericTraitService: Send + Sync + 'static {
+    type Definition: ?Sized + 'static;
+    type Implementation: Service;
+
+    /// Get the resolver downcasting the implementation to the trait.
+    fn resolver() -> ServiceResolver<Self>;
+}
+
+/// Closed instantiation declared by a dependency on an open generic service.
+/// The service container adds it as a registration of the open generic lifetime,
+/// so it is resolved and validated as the closed registrations.
+#[derive(Clone)]
+pub struct GenericInstantiation {
+    /// Key of the `Definition` type of the open generic registration.
+    pub definition: ServiceKey,
+    /// Key of the implementation type.
+    pub implementation: ServiceKey,
+    pub init: ServiceInit,
+    /// Service resolver of the generic traits.
+    pub resolver: Option<Arc<dyn Any + Send + Sync + 'static>>,
+    /// Dependencies of the implementation.
+    pub dependencies: fn() -> Vec<ServiceDependency>,
+}
+
+impl GenericInstantiation {
+    /// Instantiation of an open generic type.
+    pub fn of<T: GenericService>() -> Self {
+        Self {
+            definition: ServiceKey::of::<T::Definition>(),
+            implementation: ServiceKey::of::<T>(),
+            init: Arc::new(T::try_init),
+            resolver: None,
+            dependencies: T::dependencies,
+        }
+    }
+
+    /// Instantiation of an open generic trait.
+    pub fn of_trait<T: ?Sized + GenericTraitService>() -> Self {
+        Self {
+            definition: ServiceKey::of::<T::Definition>(),
+            implementation: ServiceKey::of::<T::Implementation>(),
+            init: Arc::new(T::Implementation::try_init),
+            resolver: Some(Arc::new(T::resolver())),
+            dependencies: T::Implementation::dependencies,
+        }
+    }
+}
+
+impl fmt::Debug for GenericInstantiation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GenericInstantiation")
+            .field("definition", &self.definition)
+            .field("implementation", &self.implementation)
+            .finish()
+    }
+}
+
+impl PartialEq for GenericInstantiation {
+    fn eq(&self, other: &Self) -> bool {
+        self.definition == other.definition && self.implementation == other.implementation
+    }
+}
+
+impl Eq for GenericInstantiation {}
+
+/// Probe of the instantiation of a field type, used by `derive(Service)`.
+/// The generic impls apply when the type is an open generic instantiation,
+/// otherwise the method resolution falls back to `ClosedDependency` on the reference.
+#[doc(hidden)]
+pub struct DependencyProbe<T: ?Sized>(PhantomData<fn(*const T)>);
+
+impl<T: ?Sized> DependencyProbe<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Default for DependencyProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait GenericDependency {
+    fn instantiation(&self) -> Option<GenericInstantiation>;
+}
+
+impl<T: GenericService> GenericDependency for DependencyProbe<T> {
+    fn instantiation(&self) -> Option<GenericInstantiation> {
+        Some(GenericInstantiation::of::<T>())
+    }
+}
+
+#[doc(hidden)]
+pub trait GenericTraitDependency {
+    fn instantiation(&self) -> Option<GenericInstantiation>;
+}
+
+impl<T: ?Sized + GenericTraitService> GenericTraitDependency for DependencyProbe<T> {
+    fn instantiation(&self) -> Option<GenericInstantiation> {
+        Some(GenericInstantiation::of_trait::<T>())
+    }
+}
+
+#[doc(hidden)]
+pub trait ClosedDependency {
+    fn instantiation(&self) -> Option<GenericInstantiation>;
+}
+
+impl<T: ?Sized> ClosedDependency for &DependencyProbe<T> {
+    fn instantiation(&self) -> Option<GenericInstantiation> {
+        None
+    }
+}
+
+/// Get an instance of the specified instantiation of an open generic type.
+/// A closed registration of the type takes precedence over the open generic one.
+pub fn get_generic_instance<T: GenericService>(
+    service_provider: &dyn ServiceProvider,
+) -> Result<Arc<T>, Error> {
+    let key = ServiceKey::of::<T>();
+
+    let service = match service_provider.get_service_definition(&key) {
+        Some(service_definition) => {
+            service_provider.get_or_create_instance_from_definition(service_definition)?
+        }
+        None => {
+            let service_definition = service_provider.get_or_create_generic_definition(
+                key,
+                &ServiceKey::of::<T::Definition>(),
+                ServiceKey::of::<T>(),
+                Arc::new(T::try_init),
+                None,
+            )?;
+
+            service_provider.get_or_create_instance_from_definition(&service_definition)?
+        }
+    };
+
+    Arc::downcast::<T>(service.as_any()).map_err(|_| Error::DowncastFailed {
+        type_name: std::any::type_name::<T>().to_string(),
+    })
+}
+
+/// Get an instance of the specified instantiation of an open generic trait.
+/// A closed registration of the trait takes precedence over the open generic one.
+pub fn get_generic_trait_instance<T: ?Sized + GenericTraitService>(
+    service_provider: &dyn ServiceProvider,
+) -> Result<Arc<T>, Error> {
+    let key = ServiceKey::of::<T>();
+
+    match service_provider.get_service_definition(&key) {
+        Some(service_definition) => {
+            resolve_trait_definition::<T>(service_provider, service_definition)
+        }
+        None => {
+            let service_definition = service_provider.get_or_create_generic_definition(
+                key,
+                &ServiceKey::of::<T::Definition>(),
+                ServiceKey::of::<T::Implementation>(),
+                Arc::new(T::Implementation::try_init),
+                Some(Arc::new(T::resolver())),
+            )?;
+
+            resolve_trait_definition::<T>(service_provider, &service_definition)
+        }
+    }
+}
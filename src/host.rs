@@ -0,0 +1,188 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::watch;
+
+use crate::{
+    async_service::get_all_trait_instances_async, error::Error,
+    root_service_provider::RootServiceProvider, service_container::ServiceContainer,
+};
+
+/// Default duration given to the hosted services to stop.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Future of a hosted service starting or stopping.
+pub type HostedFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'a>>;
+
+/// Background service started and stopped by the host.
+pub trait HostedService: Send + Sync + 'static {
+    /// Start the service, called in registration order when the host starts.
+    fn start(&self) -> HostedFuture<'_>;
+
+    /// Stop the service, called in reverse order when the host shuts down.
+    fn stop(&self) -> HostedFuture<'_>;
+}
+
+/// Handle triggering the shutdown of a host.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl ShutdownHandle {
+    /// Request the host to stop its hosted services.
+    pub fn shutdown(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Wait until the shutdown is requested.
+    async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender is owned by the handle, the channel cannot be closed
+        let _ = receiver.wait_for(|requested| *requested).await;
+    }
+}
+
+/// Service provider running the hosted services of a service container.
+pub struct Host {
+    service_provider: RootServiceProvider,
+    shutdown_timeout: Duration,
+    shutdown_handle: ShutdownHandle,
+    /// Hosted services started, in start order.
+    started_services: Mutex<Vec<Arc<dyn HostedService>>>,
+}
+
+impl Host {
+    /// Build the service provider of the host from the service container.
+    pub fn new(service_container: &ServiceContainer) -> Self {
+        Self {
+            service_provider: service_container.build(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            shutdown_handle: ShutdownHandle {
+                sender: Arc::new(watch::Sender::new(false)),
+            },
+            started_services: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Set the duration given to the hosted services to stop.
+    pub fn with_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+
+        self
+    }
+
+    /// Get the service provider of the host.
+    pub fn service_provider(&self) -> &RootServiceProvider {
+        &self.service_provider
+    }
+
+    /// Get a handle triggering the shutdown of the host.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown_handle.clone()
+    }
+
+    /// Resolve and start the hosted services in registration order.
+    /// If a service fails to start, the services already started are stopped.
+    pub async fn start(&self) -> Result<(), Error> {
+        let hosted_services =
+            get_all_trait_instances_async::<dyn HostedService>(&self.service_provider).await?;
+
+        for hosted_service in hosted_services {
+            if let Err(error) = hosted_service.start().await {
+                // The start error is reported rather than the stop errors
+                let _ = self.stop().await;
+                return Err(Error::HostedServiceFailed {
+                    source: error.into(),
+                });
+            }
+
+            self.started_services.lock().unwrap().push(hosted_service);
+        }
+
+        Ok(())
+    }
+
+    /// Stop the started services in reverse order, then dispose the singletons.
+    /// The services still stopping when the shutdown timeout elapses are abandoned.
+    pub async fn stop(&self) -> Result<(), Error> {
+        let started_services = std::mem::take(&mut *self.started_services.lock().unwrap());
+        let deadline = tokio::time::Instant::now() + self.shutdown_timeout;
+        let mut result = Ok(());
+
+        for hosted_service in started_services.into_iter().rev() {
+            let stopped = match tokio::time::timeout_at(deadline, hosted_service.stop()).await {
+                Ok(stopped) => stopped.map_err(|error| Error::HostedServiceFailed {
+                    source: error.into(),
+                }),
+                Err(_) => Err(Error::ShutdownTimeout {
+                    timeout: self.shutdown_timeout,
+                }),
+            };
+
+            // Keep the first error and stop the other services
+            if result.is_ok() {
+                result = stopped;
+            }
+        }
+
+        self.service_provider.shutdown();
+
+        result
+    }
+
+    /// Start the hosted services, wait for the shutdown handle, a Ctrl-C signal
+    /// or a SIGTERM signal on Unix, then stop them.
+    pub async fn run(&self) -> Result<(), Error> {
+        self.run_until(async {
+            tokio::select! {
+                _ = ctrl_c() => {}
+                _ = terminate() => {}
+            }
+        })
+        .await
+    }
+
+    /// Start the hosted services, wait for the shutdown handle or the shutdown future, then stop them.
+    pub async fn run_until(&self, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+        self.start().await?;
+
+        tokio::select! {
+            _ = self.shutdown_handle.wait() => {}
+            _ = shutdown => {}
+        }
+
+        self.stop().await
+    }
+}
+
+/// Wait for a Ctrl-C signal, without a signal handler only the shutdown handle stops the host.
+async fn ctrl_c() {
+    if tokio::signal::ctrl_c().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Wait for a SIGTERM signal, sent by the service managers and the container runtimes.
+#[cfg(unix)]
+async fn terminate() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            terminate.recv().await;
+        }
+        Err(_) => std::future::pending::<()>().await,
+    }
+}
+
+/// SIGTERM is not delivered on the other platforms.
+#[cfg(not(unix))]
+async fn terminate() {
+    std::future::pending::<()>().await;
+}
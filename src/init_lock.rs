@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, ThreadId},
+};
+
+use crate::{
+    error::Error,
+    resolution_chain,
+    service_definition::{RegistrationId, ServiceDefinition},
+};
+
+/// Initialization locks by registration.
+/// The first thread creating an instance holds the lock, the other threads wait for the instance.
+/// A thread waiting for a lock held by a thread which waits for this thread gets the cycle instead.
+#[derive(Default)]
+pub(crate) struct InitLocks {
+    state: Mutex<InitState>,
+    released: Condvar,
+}
+
+#[derive(Default)]
+struct InitState {
+    /// Thread creating the instance of each registration.
+    holders: HashMap<RegistrationId, ThreadId>,
+    /// Registration awaited by each waiting thread, with the name of its implementation.
+    waiting: HashMap<ThreadId, (RegistrationId, String)>,
+}
+
+/// Initialization lock held by the current thread, released when dropped.
+pub(crate) struct InitGuard<'a> {
+    init_locks: &'a InitLocks,
+    registration: RegistrationId,
+}
+
+impl InitLocks {
+    /// Acquire the initialization lock of the registration, waiting for the thread holding it.
+    /// A panic of a previous init releases the lock and lets the next thread retry.
+    pub(crate) fn acquire(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<InitGuard<'_>, Error> {
+        let registration = service_definition.registration_id();
+        let current = thread::current().id();
+        let mut state = self.lock_state();
+
+        while let Some(&holder) = state.holders.get(&registration) {
+            if let Some(waited) = state.waits_for(holder, current) {
+                return Err(resolution_chain::cycle_across_threads(waited));
+            }
+
+            let name = service_definition.implementation.to_string();
+            state.waiting.insert(current, (registration, name));
+            state = self
+                .released
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+            state.waiting.remove(&current);
+        }
+        state.holders.insert(registration, current);
+
+        Ok(InitGuard {
+            init_locks: self,
+            registration,
+        })
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, InitState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl InitState {
+    /// Get the registrations awaited from the holder until the current thread,
+    /// none if the holder does not wait for the current thread.
+    fn waits_for(
+        &self,
+        mut holder: ThreadId,
+        current: ThreadId,
+    ) -> Option<Vec<(RegistrationId, String)>> {
+        let mut waited = Vec::new();
+
+        while let Some((registration, name)) = self.waiting.get(&holder) {
+            waited.push((*registration, name.clone()));
+            holder = *self.holders.get(registration)?;
+
+            if holder == current {
+                return Some(waited);
+            }
+            // The threads of a cycle which does not include the current thread detected it already
+            if waited.len() > self.waiting.len() {
+                return None;
+            }
+        }
+
+        None
+    }
+}
+
+impl Drop for InitGuard<'_> {
+    fn drop(&mut self) {
+        self.init_locks
+            .lock_state()
+            .holders
+            .remove(&self.registration);
+        self.init_locks.released.notify_all();
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    ops::Deref,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    error::Error,
+    service::{Service, ServiceProvider, get_instance, get_trait_instance},
+};
+
+/// Function resolving the instance of a lazy dependency.
+type LazyResolve<T> = Box<dyn Fn(&dyn ServiceProvider) -> Result<Arc<T>, Error> + Send + Sync>;
+
+/// Dependency created on first use by the service provider it was resolved from.
+/// A scoped dependency is created in the scope of the lazy,
+/// the lazy does not keep the provider alive.
+pub struct Lazy<T: ?Sized + Send + Sync + 'static> {
+    service_provider: Arc<dyn ServiceProvider + Send + Sync>,
+    resolve: LazyResolve<T>,
+    instance: OnceLock<Arc<T>>,
+}
+
+impl<T: ?Sized + Send + Sync + 'static> Lazy<T> {
+    /// Create a lazy dependency resolved by the function.
+    pub fn new<F>(service_provider: &dyn ServiceProvider, resolve: F) -> Self
+    where
+        F: Fn(&dyn ServiceProvider) -> Result<Arc<T>, Error> + Send + Sync + 'static,
+    {
+        Self {
+            service_provider: service_provider.clone_provider(),
+            resolve: Box::new(resolve),
+            instance: OnceLock::new(),
+        }
+    }
+
+    /// Get the instance, creating it on first use.
+    /// A failed creation is retried on the next call.
+    pub fn get(&self) -> Result<Arc<T>, Error> {
+        if let Some(instance) = self.instance.get() {
+            return Ok(instance.clone());
+        }
+
+        let instance = (self.resolve)(self.service_provider.as_service_provider())?;
+
+        Ok(self.instance.get_or_init(|| instance).clone())
+    }
+
+    /// Check if the instance is created.
+    pub fn is_created(&self) -> bool {
+        self.instance.get().is_some()
+    }
+}
+
+impl<T: ?Sized + Send + Sync + 'static> Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get().expect("Cannot create lazy service");
+
+        self.instance.get().expect("Lazy service is not created")
+    }
+}
+
+/// Get a lazy instance of the specified type.
+pub fn get_lazy_instance<T: Service + 'static>(service_provider: &dyn ServiceProvider) -> Lazy<T> {
+    Lazy::new(service_provider, get_instance::<T>)
+}
+
+/// Get a lazy instance of the specified trait.
+pub fn get_lazy_trait_instance<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> Lazy<T> {
+    Lazy::new(service_provider, get_trait_instance::<T>)
+}
@@ -1,8 +1,34 @@
+#[cfg(feature = "async")]
+pub mod async_service;
+pub mod condition;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod decorator;
+#[cfg(feature = "discovery")]
+pub mod discovery;
+pub mod dispose;
 pub mod error;
+pub mod factory;
+pub mod generic_service;
+#[cfg(feature = "host")]
+pub mod host;
+mod init_lock;
+pub mod lazy;
 pub mod macros;
+pub mod options;
+pub mod registration_policy;
+mod resolution_chain;
 pub mod root_service_provider;
 pub mod scoped_service_provider;
 pub mod service;
 pub mod service_container;
 pub mod service_definition;
+pub mod service_dependency;
+pub mod service_graph;
+pub mod service_key;
 pub mod service_lifetime;
+pub mod service_module;
+pub mod validation;
+
+#[cfg(feature = "discovery")]
+pub use shive_derive::service;
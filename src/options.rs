@@ -0,0 +1,59 @@
+use std::{any::Any, ops::Deref, sync::Arc};
+
+use crate::service::{Service, ServiceProvider};
+
+/// Check of a configuration when the service container is validated.
+pub trait Validate {
+    /// Return a message describing the invalid values.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Typed configuration registered in the service container.
+pub struct Options<T: Send + Sync + 'static> {
+    value: T,
+}
+
+impl<T: Send + Sync + 'static> Options<T> {
+    /// Create options from the configuration value.
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+
+    /// Get the configuration value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Send + Sync + 'static> Deref for Options<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Send + Sync + 'static> Service for Options<T> {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        unreachable!("Options are registered with their value")
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+/// Validate the options of an unmanaged instance.
+pub(crate) fn validate_options<T: Validate + Send + Sync + 'static>(
+    instance: &(dyn Any + Send + Sync),
+) -> Result<(), String> {
+    match instance.downcast_ref::<Options<T>>() {
+        Some(options) => options.value.validate(),
+        None => Err("Cannot downcast options".to_string()),
+    }
+}
@@ -0,0 +1,12 @@
+/// Behavior of the service container when an implementation is declared twice for the same service.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RegistrationPolicy {
+    /// Keep both registrations, the last one is resolved.
+    #[default]
+    Allow,
+    /// Keep both registrations and report them in `ServiceContainer::warnings`.
+    Warn,
+    /// Keep both registrations and report them as errors of `ServiceContainer::validate`.
+    /// The registrations are not rejected, `build` ignores them while `build_validated` fails.
+    Error,
+}
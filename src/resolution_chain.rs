@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+#[cfg(feature = "async")]
+use std::sync::Arc;
+
+use crate::{
+    error::Error,
+    service_definition::{RegistrationId, ServiceDefinition},
+    service_key::ServiceKey,
+};
+
+thread_local! {
+    /// Registrations being created by the current thread, identified by container and registration,
+    /// with the name of their implementation.
+    static RESOLUTION_CHAIN: RefCell<Vec<(RegistrationId, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Registration being created by the current thread, removed from the chain when dropped.
+pub(crate) struct ResolutionGuard;
+
+/// Add the registration to the resolution chain of the thread before calling its init function.
+/// A registration already in the chain depends on itself.
+pub(crate) fn enter(service_definition: &ServiceDefinition) -> Result<ResolutionGuard, Error> {
+    let registration = service_definition.registration_id();
+    let name = service_definition.implementation.to_string();
+
+    RESOLUTION_CHAIN.with_borrow_mut(|chain| {
+        check_cycle(chain, registration, &name)?;
+        chain.push((registration, name));
+
+        Ok(ResolutionGuard)
+    })
+}
+
+/// Get the cycle error if the registration is already in the chain.
+fn check_cycle(
+    chain: &[(RegistrationId, String)],
+    registration: RegistrationId,
+    name: &str,
+) -> Result<(), Error> {
+    match chain.iter().position(|(active, _)| *active == registration) {
+        Some(start) => {
+            let mut path: Vec<String> = chain[start..]
+                .iter()
+                .map(|(_, name)| name.clone())
+                .collect();
+            path.push(name.to_string());
+
+            Err(Error::Cycle { path })
+        }
+        None => Ok(()),
+    }
+}
+
+/// Registrations being created asynchronously, carried by the provider handles
+/// passed to the asynchronous init functions since their tasks are not bound to a thread.
+#[cfg(feature = "async")]
+#[derive(Clone, Default)]
+pub(crate) struct AsyncChain(Arc<Vec<(RegistrationId, String)>>);
+
+#[cfg(feature = "async")]
+impl AsyncChain {
+    /// Get the chain followed by the registration before awaiting its initialization.
+    /// A registration already in the chain depends on itself.
+    pub(crate) fn enter(&self, service_definition: &ServiceDefinition) -> Result<Self, Error> {
+        let registration = service_definition.registration_id();
+        let name = service_definition.implementation.to_string();
+        check_cycle(&self.0, registration, &name)?;
+
+        let mut chain = (*self.0).clone();
+        chain.push((registration, name));
+
+        Ok(Self(Arc::new(chain)))
+    }
+}
+
+/// Get the cycle of registrations awaited by other threads, the last one being created by the current thread.
+pub(crate) fn cycle_across_threads(waited: Vec<(RegistrationId, String)>) -> Error {
+    RESOLUTION_CHAIN.with_borrow(|chain| {
+        let start = waited
+            .last()
+            .and_then(|(registration, _)| {
+                chain.iter().position(|(active, _)| active == registration)
+            })
+            .unwrap_or(0);
+        let path = chain[start..]
+            .iter()
+            .map(|(_, name)| name.clone())
+            .chain(waited.into_iter().map(|(_, name)| name))
+            .collect();
+
+        Error::Cycle { path }
+    })
+}
+
+impl Drop for ResolutionGuard {
+    fn drop(&mut self) {
+        RESOLUTION_CHAIN.with_borrow_mut(|chain| chain.pop());
+    }
+}
+
+/// Add the services being created by the thread to the error of a dependency.
+/// The errors already carrying their path are kept as is.
+pub(crate) fn with_context(error: Error, requested: &ServiceKey) -> Error {
+    if matches!(error, Error::Resolution { .. } | Error::Cycle { .. }) {
+        return error;
+    }
+
+    RESOLUTION_CHAIN.with_borrow(|chain| {
+        if chain.is_empty() {
+            return error;
+        }
+
+        let mut path: Vec<String> = chain.iter().map(|(_, name)| name.clone()).collect();
+        path.push(requested.to_string());
+
+        Error::Resolution {
+            path,
+            source: Box::new(error),
+        }
+    })
+}
@@ -1,118 +1,370 @@
 use std::{
+    any::Any,
     collections::HashMap,
     sync::{Arc, RwLock},
 };
 
+#[cfg(feature = "async")]
 use crate::{
+    async_service::{AsyncInstance, AsyncInstanceCell},
+    resolution_chain::AsyncChain,
+};
+#[cfg(feature = "async")]
+use std::sync::Mutex;
+
+use crate::{
+    dispose::{Disposables, Owner},
+    init_lock::InitLocks,
+    resolution_chain,
     scoped_service_provider::ScopedServiceProvider,
     service::{Service, ServiceProvider},
+    service_definition::{RegistrationId, ServiceDefinition, ServiceInit},
+    service_key::ServiceKey,
     service_lifetime::ServiceLifetime,
 };
 
-use super::{error::Error, service_container::ServiceContainer};
+use super::{
+    error::Error,
+    service_container::{ServiceCollection, ServiceContainer},
+};
 
-/// Service provider providing singleton and unmanaged services
+/// Service provider providing singleton and unmanaged services.
+/// The clones of the provider share the singleton instances.
 #[derive(Clone)]
-pub struct RootServiceProvider<'a> {
-    pub service_container: &'a ServiceContainer,
-    pub singleton_services: Arc<RwLock<HashMap<String, Arc<dyn Service>>>>,
+pub struct RootServiceProvider {
+    pub service_container: Arc<ServiceContainer>,
+    /// Registrations enabled when the provider was built.
+    service_collection: Arc<ServiceCollection>,
+    /// Singleton instances by registration identifier.
+    pub singleton_services: Arc<RwLock<HashMap<RegistrationId, Arc<dyn Service>>>>,
+    /// Closed definitions materialized from the open generic registrations.
+    pub generic_services: Arc<RwLock<HashMap<ServiceKey, ServiceDefinition>>>,
+    /// Identifier of the first closed definition materialized on demand.
+    generic_id: usize,
+    /// Provider of the registrations not declared in the service container.
+    pub parent: Option<Arc<RootServiceProvider>>,
+    /// Disposable singletons, disposed when the last clone owning the provider is dropped.
+    disposables: Arc<Disposables>,
+    /// Locks creating each singleton once.
+    init_locks: Arc<InitLocks>,
+    /// Initializations of the asynchronous singletons by registration identifier.
+    #[cfg(feature = "async")]
+    async_singletons: Arc<Mutex<HashMap<RegistrationId, AsyncInstanceCell>>>,
+    /// Asynchronous initializations awaiting the services resolved by this handle.
+    #[cfg(feature = "async")]
+    async_chain: AsyncChain,
+    /// Teardown of the provider, shared by the clones owning it.
+    _owner: Option<Arc<Owner>>,
 }
 
-impl<'a> RootServiceProvider<'a> {
+impl RootServiceProvider {
     /// Create service manger from service collection.
-    pub fn new(sc: &'a ServiceContainer) -> Self {
-        Self {
-            service_container: sc,
+    pub fn new(sc: &ServiceContainer) -> Self {
+        let service_collection = sc.get_enabled_service_collection();
+        // The instantiations declared by the dependencies are numbered after the registrations
+        let generic_id = service_collection
+            .values()
+            .flatten()
+            .map(|service_definition| service_definition.id + 1)
+            .fold(sc.registration_count(), usize::max);
+
+        let service_provider = Self {
+            service_container: Arc::new(sc.snapshot()),
+            service_collection: Arc::new(service_collection),
             singleton_services: Arc::new(RwLock::new(HashMap::new())),
+            generic_services: Arc::new(RwLock::new(HashMap::new())),
+            generic_id,
+            parent: None,
+            disposables: Arc::new(Disposables::default()),
+            init_locks: Arc::new(InitLocks::default()),
+            #[cfg(feature = "async")]
+            async_singletons: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "async")]
+            async_chain: AsyncChain::default(),
+            _owner: None,
+        };
+        let handle = service_provider.clone();
+
+        Self {
+            _owner: Some(Arc::new(Owner::new(move || handle.shutdown()))),
+            ..service_provider
+        }
+    }
+
+    /// Get a clone not owning the provider, held by the services it creates.
+    fn handle(&self) -> Self {
+        Self {
+            _owner: None,
+            ..self.clone()
+        }
+    }
+
+    /// Get a handle resolving the services awaited by the asynchronous initializations of the chain.
+    #[cfg(feature = "async")]
+    pub(crate) fn with_async_chain(&self, async_chain: AsyncChain) -> Self {
+        Self {
+            async_chain,
+            ..self.handle()
+        }
+    }
+
+    /// Dispose the singletons in reverse creation order and clear them.
+    pub fn shutdown(&self) {
+        self.singleton_services.write().unwrap().clear();
+        #[cfg(feature = "async")]
+        self.async_singletons.lock().unwrap().clear();
+        self.disposables.dispose();
+    }
+
+    /// Create a child provider from another service container.
+    /// The registrations of the child container override the ones of this provider,
+    /// the other registrations and the singletons already created are shared with this provider.
+    pub fn create_child(&self, sc: &ServiceContainer) -> RootServiceProvider {
+        RootServiceProvider {
+            parent: Some(Arc::new(self.clone())),
+            ..RootServiceProvider::new(sc)
         }
     }
 
+    /// Get the provider built from the container declaring the registration, this provider by default.
+    fn get_declaring_provider(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> &RootServiceProvider {
+        let mut service_provider = self;
+
+        while service_provider.service_container.id() != service_definition.container_id {
+            match &service_provider.parent {
+                Some(parent) => service_provider = parent,
+                None => return self,
+            }
+        }
+
+        service_provider
+    }
+
     /// Create service manger from service collection.
     pub fn create_scope(&self) -> ScopedServiceProvider {
-        ScopedServiceProvider::new(self)
+        ScopedServiceProvider::new(self.clone())
     }
 }
 
-impl<'a> ServiceProvider<'a> for RootServiceProvider<'a> {
-    fn as_service_provider(&'a self) -> &'a dyn ServiceProvider<'a> {
+impl ServiceProvider for RootServiceProvider {
+    fn as_service_provider(&self) -> &dyn ServiceProvider {
         self
     }
 
-    fn get_or_create_instance(&self, type_name: String) -> Result<Arc<dyn Service>, Error> {
-        // Get service definition
-        let service_definition = self
-            .service_container
-            .get_service_definition_from_key(type_name.clone());
+    fn clone_provider(&self) -> Arc<dyn ServiceProvider + Send + Sync> {
+        Arc::new(self.handle())
+    }
 
-        if service_definition.is_none() {
-            return Err(Error::Internal("Service definition not found".to_string()));
+    fn get_or_create_instance_from_definition(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<Arc<dyn Service>, Error> {
+        // Singleton and unmanaged services inherited from the parent are shared with it
+        let declaring_provider = self.get_declaring_provider(service_definition);
+        if !std::ptr::eq(declaring_provider, self) {
+            return declaring_provider.get_or_create_instance_from_definition(service_definition);
         }
 
-        let service_definition = service_definition.unwrap();
-
         // If the service instance exists, return it
         // Search in unmanaged services
         if matches!(service_definition.lifetime, ServiceLifetime::Unmanaged) {
             let unmanaged_services = &self.service_container.unmanaged_services;
-            if unmanaged_services.contains_key(&type_name) {
-                let srv = unmanaged_services.get(&type_name);
-
-                return match srv {
-                    Some(service) => Ok(service.clone()),
-                    None => Err(Error::Internal(format!(
-                        "Cannot get the service instance for {}",
-                        type_name
-                    ))),
-                };
-            } else {
-                return Err(Error::Internal(format!(
-                    "Unmanaged instance for {} is not set",
-                    type_name,
-                )));
-            }
+            return match unmanaged_services.get(&service_definition.id) {
+                Some(service) => Ok(service.clone()),
+                None => Err(Error::NotRegistered {
+                    type_name: service_definition.implementation.to_string(),
+                }),
+            };
         }
 
         // Search in singleton
         if matches!(service_definition.lifetime, ServiceLifetime::Singleton) {
             let singleton_services = self.singleton_services.read().unwrap();
 
-            if singleton_services.contains_key(&type_name) {
-                let srv = singleton_services.get(&type_name);
-
-                return match srv {
-                    Some(service) => Ok(service.clone()),
-                    None => Err(Error::Internal(format!(
-                        "Cannot get the service instance for {}",
-                        type_name
-                    ))),
-                };
+            if let Some(service) = singleton_services.get(&service_definition.registration_id()) {
+                return Ok(service.clone());
             }
 
             // Unlock the singleton services
             drop(singleton_services);
 
-            // Create a new service instance
+            if service_definition.async_init.is_some() {
+                return Err(Error::AsyncInitRequired {
+                    type_name: service_definition.implementation.to_string(),
+                });
+            }
+
+            // Detect the cycles before waiting for an instance created by this thread
+            let _resolution_guard = resolution_chain::enter(service_definition)?;
+
+            // Create the instance once, the other threads wait for it
+            let _init_guard = self.init_locks.acquire(service_definition)?;
+            if let Some(service) = self
+                .singleton_services
+                .read()
+                .unwrap()
+                .get(&service_definition.registration_id())
+            {
+                return Ok(service.clone());
+            }
+
+            // Create a new service instance, a failed initialization is not cached
             let init = service_definition.init.clone();
-            let service = init(self);
+            let service = init(self)?;
 
-            // Add new instance for scoped and singleton
             self.singleton_services
                 .write()
                 .expect("blocked")
-                .insert(type_name, service.clone());
+                .insert(service_definition.registration_id(), service.clone());
+            self.disposables.push(&service);
 
-            return Ok(service.clone());
+            Ok(service)
         } else {
             // Scoped or transient services are not supported in root service provider
             // because it needs a scope. Instead, get the service from a service provider.
-            return Err(Error::Internal(format!(
-                "Cannot get the service instance for {} (scoped or transient services are not supported in root service provider)",
-                type_name
-            )));
+            Err(Error::LifetimeMismatch {
+                requested: service_definition.implementation.to_string(),
+                lifetime: service_definition.lifetime,
+            })
         }
     }
 
+    fn create_instance_from_definition(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<Arc<dyn Service>, Error> {
+        let service = (service_definition.init)(self)?;
+        self.disposables.push(&service);
+
+        Ok(service)
+    }
+
+    #[cfg(feature = "async")]
+    fn get_or_create_instance_from_definition_async(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> AsyncInstance {
+        let service_provider = self.clone();
+        let service_definition = service_definition.clone();
+
+        Box::pin(async move {
+            // Singleton and unmanaged services inherited from the parent are shared with it
+            let declaring_provider = service_provider.get_declaring_provider(&service_definition);
+            if !std::ptr::eq(declaring_provider, &service_provider) {
+                return declaring_provider
+                    .with_async_chain(service_provider.async_chain.clone())
+                    .get_or_create_instance_from_definition_async(&service_definition)
+                    .await;
+            }
+
+            // Synchronous services and lifetimes not created by the root are resolved synchronously
+            let Some(async_init) = service_definition.async_init.clone() else {
+                return service_provider
+                    .get_or_create_instance_from_definition(&service_definition);
+            };
+            if !matches!(service_definition.lifetime, ServiceLifetime::Singleton) {
+                return service_provider
+                    .get_or_create_instance_from_definition(&service_definition);
+            }
+
+            // Detect the cycles before awaiting an initialization depending on this one
+            let async_chain = service_provider.async_chain.enter(&service_definition)?;
+
+            // Initialize the singleton once, the concurrent calls await the same initialization
+            let id = service_definition.registration_id();
+            let cell = service_provider
+                .async_singletons
+                .lock()
+                .unwrap()
+                .entry(id)
+                .or_default()
+                .clone();
+            let service = cell
+                .get_or_try_init(|| async {
+                    let service =
+                        async_init(Arc::new(service_provider.with_async_chain(async_chain)))
+                            .await?;
+                    service_provider
+                        .singleton_services
+                        .write()
+                        .unwrap()
+                        .insert(id, service.clone());
+                    service_provider.disposables.push(&service);
+
+                    Ok::<_, Error>(service)
+                })
+                .await?;
+
+            Ok(service.clone())
+        })
+    }
+
     fn get_service_container(&self) -> &ServiceContainer {
-        self.service_container
+        &self.service_container
+    }
+
+    fn get_service_definitions(&self, key: &ServiceKey) -> &[ServiceDefinition] {
+        let service_definitions = self
+            .service_collection
+            .get(key)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        match &self.parent {
+            Some(parent) if service_definitions.is_empty() => parent.get_service_definitions(key),
+            _ => service_definitions,
+        }
+    }
+
+    fn get_or_create_generic_definition(
+        &self,
+        key: ServiceKey,
+        open_key: &ServiceKey,
+        implementation: ServiceKey,
+        init: ServiceInit,
+        resolver: Option<Arc<dyn Any + Send + Sync + 'static>>,
+    ) -> Result<ServiceDefinition, Error> {
+        // If the closed definition exists, return it
+        if let Some(service_definition) = self.generic_services.read().unwrap().get(&key) {
+            return Ok(service_definition.clone());
+        }
+
+        let Some(lifetime) = self.service_container.get_open_generic_lifetime(open_key) else {
+            // Fall back to the open generic registration of the parent
+            return match &self.parent {
+                Some(parent) => parent.get_or_create_generic_definition(
+                    key,
+                    open_key,
+                    implementation,
+                    init,
+                    resolver,
+                ),
+                None => Err(Error::NotRegistered {
+                    type_name: open_key.to_string(),
+                }),
+            };
+        };
+
+        // Materialize the closed definition after the container registrations
+        let mut generic_services = self.generic_services.write().unwrap();
+        let id = self.generic_id + generic_services.len();
+        let service_definition = generic_services.entry(key).or_insert(ServiceDefinition {
+            id,
+            container_id: self.service_container.id(),
+            implementation,
+            lifetime,
+            init,
+            async_init: None,
+            resolver,
+            // Closed definitions are created after the validation of the container
+            dependencies: Vec::new(),
+            condition: None,
+            validator: None,
+        });
+
+        Ok(service_definition.clone())
     }
 }
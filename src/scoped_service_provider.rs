@@ -1,77 +1,254 @@
 use std::{
+    any::Any,
     collections::HashMap,
     sync::{Arc, RwLock},
 };
 
+#[cfg(feature = "async")]
 use crate::{
+    async_service::{AsyncInstance, AsyncInstanceCell},
+    resolution_chain::AsyncChain,
+};
+#[cfg(feature = "async")]
+use std::sync::Mutex;
+
+use crate::{
+    dispose::{Disposables, Owner},
+    init_lock::InitLocks,
+    resolution_chain,
     root_service_provider::RootServiceProvider,
     service::{Service, ServiceProvider},
+    service_definition::{RegistrationId, ServiceDefinition, ServiceInit},
+    service_key::ServiceKey,
     service_lifetime::ServiceLifetime,
 };
 
 use super::error::Error;
 
+/// Service provider creating the scoped services.
+/// The clones of the scope share the scoped instances and can be moved across threads.
 #[derive(Clone)]
-pub struct ScopedServiceProvider<'a> {
-    pub services: Arc<RwLock<HashMap<String, Arc<dyn Service>>>>,
-    pub root: &'a RootServiceProvider<'a>,
+pub struct ScopedServiceProvider {
+    /// Scoped instances by service container and registration identifiers.
+    pub services: Arc<RwLock<HashMap<RegistrationId, Arc<dyn Service>>>>,
+    pub root: RootServiceProvider,
+    /// Disposable scoped and transient instances, disposed when the last clone owning the scope is dropped.
+    disposables: Arc<Disposables>,
+    /// Locks creating each scoped instance once.
+    init_locks: Arc<InitLocks>,
+    /// Initializations of the asynchronous scoped instances by service container and registration identifiers.
+    #[cfg(feature = "async")]
+    async_services: Arc<Mutex<HashMap<RegistrationId, AsyncInstanceCell>>>,
+    /// Asynchronous initializations awaiting the services resolved by this handle.
+    #[cfg(feature = "async")]
+    async_chain: AsyncChain,
+    /// Teardown of the scope, shared by the clones owning it.
+    _owner: Option<Arc<Owner>>,
 }
 
-impl<'a> ScopedServiceProvider<'a> {
+impl ScopedServiceProvider {
     /// Create service manger from service collection.
-    pub fn new(root: &'a RootServiceProvider) -> Self {
-        ScopedServiceProvider {
+    pub fn new(root: RootServiceProvider) -> Self {
+        let scope = ScopedServiceProvider {
             services: Arc::new(RwLock::new(HashMap::new())),
             root,
+            disposables: Arc::new(Disposables::default()),
+            init_locks: Arc::new(InitLocks::default()),
+            #[cfg(feature = "async")]
+            async_services: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "async")]
+            async_chain: AsyncChain::default(),
+            _owner: None,
+        };
+        let handle = scope.clone();
+
+        ScopedServiceProvider {
+            _owner: Some(Arc::new(Owner::new(move || handle.dispose()))),
+            ..scope
         }
     }
-}
 
-impl<'a> ServiceProvider<'a> for ScopedServiceProvider<'a> {
-    /// Get or create an instance
-    fn get_or_create_instance(&self, type_name: String) -> Result<Arc<dyn Service>, Error> {
-        // Get service definition
-        let service_definition = self
-            .get_service_container()
-            .get_service_definition_from_key(type_name.clone());
-
-        if service_definition.is_none() {
-            return Err(Error::Internal("Service definition not found".to_string()));
+    /// Get a clone not owning the scope, held by the services it creates.
+    fn handle(&self) -> Self {
+        ScopedServiceProvider {
+            _owner: None,
+            ..self.clone()
         }
+    }
 
-        let service_definition = service_definition.unwrap();
+    /// Get a handle resolving the services awaited by the asynchronous initializations of the chain.
+    #[cfg(feature = "async")]
+    fn with_async_chain(&self, async_chain: AsyncChain) -> Self {
+        ScopedServiceProvider {
+            root: self.root.with_async_chain(async_chain.clone()),
+            async_chain,
+            ..self.handle()
+        }
+    }
 
+    /// Dispose the scoped and transient instances in reverse creation order and clear them.
+    pub fn dispose(&self) {
+        self.services.write().unwrap().clear();
+        #[cfg(feature = "async")]
+        self.async_services.lock().unwrap().clear();
+        self.disposables.dispose();
+    }
+}
+
+impl ServiceProvider for ScopedServiceProvider {
+    /// Get or create an instance
+    fn get_or_create_instance_from_definition(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<Arc<dyn Service>, Error> {
         // If the service is a singleton or unmanaged,
         // get the service in the root provider
         if matches!(service_definition.lifetime, ServiceLifetime::Unmanaged)
             || matches!(service_definition.lifetime, ServiceLifetime::Singleton)
         {
-            return self.root.get_or_create_instance(type_name);
+            return self
+                .root
+                .get_or_create_instance_from_definition(service_definition);
+        }
+
+        // If the scoped instance exists, return it
+        let registration = service_definition.registration_id();
+        let is_scoped = matches!(service_definition.lifetime, ServiceLifetime::Scoped);
+        if is_scoped && let Some(service) = self.services.read().unwrap().get(&registration) {
+            return Ok(service.clone());
+        }
+
+        if service_definition.async_init.is_some() {
+            return Err(Error::AsyncInitRequired {
+                type_name: service_definition.implementation.to_string(),
+            });
         }
 
-        // Create a new service instance
+        // Detect the cycles before waiting for an instance created by this thread
+        let _resolution_guard = resolution_chain::enter(service_definition)?;
+
+        // Create the scoped instance once, the other threads wait for it
+        let _init_guard = if is_scoped {
+            Some(self.init_locks.acquire(service_definition)?)
+        } else {
+            None
+        };
+        if is_scoped && let Some(service) = self.services.read().unwrap().get(&registration) {
+            return Ok(service.clone());
+        }
+
+        // Create a new service instance, a failed initialization is not cached
         let init = service_definition.init.clone();
-        let service = init(self.as_service_provider());
-
-        // Add new instance for scoped and singleton
-        match service_definition.lifetime {
-            ServiceLifetime::Scoped => {
-                self.services
-                    .write()
-                    .unwrap()
-                    .insert(type_name, service.clone());
-            }
-            _ => {}
+        let service = init(self.as_service_provider())?;
+
+        if is_scoped {
+            self.services
+                .write()
+                .unwrap()
+                .insert(registration, service.clone());
         }
+        self.disposables.push(&service);
 
         Ok(service)
     }
 
-    fn as_service_provider(&'a self) -> &'a dyn ServiceProvider<'a> {
+    fn create_instance_from_definition(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<Arc<dyn Service>, Error> {
+        let service = (service_definition.init)(self.as_service_provider())?;
+        self.disposables.push(&service);
+
+        Ok(service)
+    }
+
+    #[cfg(feature = "async")]
+    fn get_or_create_instance_from_definition_async(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> AsyncInstance {
+        // If the service is a singleton or unmanaged,
+        // get the service in the root provider
+        if matches!(service_definition.lifetime, ServiceLifetime::Unmanaged)
+            || matches!(service_definition.lifetime, ServiceLifetime::Singleton)
+        {
+            return self
+                .root
+                .get_or_create_instance_from_definition_async(service_definition);
+        }
+
+        let scope = self.clone();
+        let service_definition = service_definition.clone();
+
+        Box::pin(async move {
+            let Some(async_init) = service_definition.async_init.clone() else {
+                return scope.get_or_create_instance_from_definition(&service_definition);
+            };
+
+            // Detect the cycles before awaiting an initialization depending on this one
+            let async_chain = scope.async_chain.enter(&service_definition)?;
+
+            // Create a new transient instance
+            if !matches!(service_definition.lifetime, ServiceLifetime::Scoped) {
+                let service = async_init(Arc::new(scope.with_async_chain(async_chain))).await?;
+                scope.disposables.push(&service);
+
+                return Ok(service);
+            }
+
+            // Initialize the scoped instance once, the concurrent calls await the same initialization
+            let registration = service_definition.registration_id();
+            let cell = scope
+                .async_services
+                .lock()
+                .unwrap()
+                .entry(registration)
+                .or_default()
+                .clone();
+            let service = cell
+                .get_or_try_init(|| async {
+                    let service = async_init(Arc::new(scope.with_async_chain(async_chain))).await?;
+                    scope
+                        .services
+                        .write()
+                        .unwrap()
+                        .insert(registration, service.clone());
+                    scope.disposables.push(&service);
+
+                    Ok::<_, Error>(service)
+                })
+                .await?;
+
+            Ok(service.clone())
+        })
+    }
+
+    fn as_service_provider(&self) -> &dyn ServiceProvider {
         self
     }
 
+    fn clone_provider(&self) -> Arc<dyn ServiceProvider + Send + Sync> {
+        Arc::new(self.handle())
+    }
+
     fn get_service_container(&self) -> &crate::service_container::ServiceContainer {
-        self.root.service_container
+        &self.root.service_container
+    }
+
+    fn get_service_definitions(&self, key: &ServiceKey) -> &[ServiceDefinition] {
+        self.root.get_service_definitions(key)
+    }
+
+    fn get_or_create_generic_definition(
+        &self,
+        key: ServiceKey,
+        open_key: &ServiceKey,
+        implementation: ServiceKey,
+        init: ServiceInit,
+        resolver: Option<Arc<dyn Any + Send + Sync + 'static>>,
+    ) -> Result<ServiceDefinition, Error> {
+        // Closed definitions are shared by all the scopes of the root provider
+        self.root
+            .get_or_create_generic_definition(key, open_key, implementation, init, resolver)
     }
 }
@@ -1,6 +1,16 @@
 use std::{any::Any, sync::Arc};
 
-use crate::{error::Error, service_container::ServiceContainer};
+#[cfg(feature = "async")]
+use crate::async_service::AsyncInstance;
+use crate::{
+    dispose::Dispose,
+    error::Error,
+    resolution_chain,
+    service_container::ServiceContainer,
+    service_definition::{ServiceDefinition, ServiceInit},
+    service_dependency::ServiceDependency,
+    service_key::ServiceKey,
+};
 
 /// Service trait
 pub trait Service: Send + Sync + 'static {
@@ -8,17 +18,89 @@ pub trait Service: Send + Sync + 'static {
     where
         Self: Sized;
 
+    /// Create the service, failing instead of panicking when a dependency cannot be resolved.
+    /// The service providers create the services with this function.
+    fn try_init(service_provider: &dyn ServiceProvider) -> Result<Arc<dyn Service>, Error>
+    where
+        Self: Sized,
+    {
+        Ok(Self::init(service_provider))
+    }
+
     fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    /// Disposable view of the service, disposed with the provider owning the instance.
+    fn as_disposable(self: Arc<Self>) -> Option<Arc<dyn Dispose>> {
+        None
+    }
+
+    /// Services resolved by `init`, used to validate the service container.
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
 }
 
 pub struct ServiceResolver<T: ?Sized + 'static> {
     pub as_interface: fn(service: Arc<dyn Any + Sync + Send + 'static>) -> Arc<T>,
 }
 
-pub trait ServiceProvider<'a> {
-    fn as_service_provider(&'a self) -> &'a dyn ServiceProvider<'a>;
-    fn get_or_create_instance(&self, type_name: String) -> Result<Arc<dyn Service>, Error>;
+pub trait ServiceProvider {
+    fn as_service_provider(&self) -> &dyn ServiceProvider;
+
+    /// Get an owned handle on the provider, sharing its instances.
+    fn clone_provider(&self) -> Arc<dyn ServiceProvider + Send + Sync>;
+    fn get_or_create_instance_from_definition(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<Arc<dyn Service>, Error>;
+
+    /// Create a new instance of the registration, disposed with the provider.
+    fn create_instance_from_definition(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> Result<Arc<dyn Service>, Error>;
+
+    /// Get or create an instance of the registration, awaiting its asynchronous initialization.
+    #[cfg(feature = "async")]
+    fn get_or_create_instance_from_definition_async(
+        &self,
+        service_definition: &ServiceDefinition,
+    ) -> AsyncInstance;
     fn get_service_container(&self) -> &ServiceContainer;
+
+    /// Get all the definitions of the key in registration order,
+    /// falling back to the parent provider if the key is not declared in the service container.
+    fn get_service_definitions(&self, key: &ServiceKey) -> &[ServiceDefinition];
+
+    /// Get the last definition of the key.
+    fn get_service_definition(&self, key: &ServiceKey) -> Option<&ServiceDefinition> {
+        self.get_service_definitions(key).last()
+    }
+
+    /// Get or create the closed definition of a service registered as an open generic.
+    fn get_or_create_generic_definition(
+        &self,
+        key: ServiceKey,
+        open_key: &ServiceKey,
+        implementation: ServiceKey,
+        init: ServiceInit,
+        resolver: Option<Arc<dyn Any + Send + Sync + 'static>>,
+    ) -> Result<ServiceDefinition, Error>;
+
+    /// Get or create an instance of the last registration of the key.
+    fn get_or_create_instance(&self, key: ServiceKey) -> Result<Arc<dyn Service>, Error> {
+        match self.get_service_definition(&key) {
+            Some(service_definition) => {
+                self.get_or_create_instance_from_definition(service_definition)
+            }
+            None => Err(Error::NotRegistered {
+                type_name: key.to_string(),
+            }),
+        }
+    }
 }
 
 /// Get an instance of the specified type.
@@ -26,44 +108,166 @@ pub trait ServiceProvider<'a> {
 pub fn get_instance<T: Send + Sync + Service + 'static>(
     service_provider: &dyn ServiceProvider,
 ) -> Result<Arc<T>, Error> {
-    let type_name = std::any::type_name::<T>().to_string();
-    let service = service_provider.get_or_create_instance(type_name);
+    resolve_instance::<T>(service_provider, ServiceKey::of::<T>())
+}
+
+/// Get an instance of the specified type registered under the key.
+/// Initialize new object depending on the lifetime.
+pub fn get_keyed_instance<T: Send + Sync + Service + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> Result<Arc<T>, Error> {
+    resolve_instance::<T>(service_provider, ServiceKey::named::<T>(key))
+}
+
+/// Get an instance of the specified trait.
+/// Initialize new object depending on the lifetime.
+pub fn get_trait_instance<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> Result<Arc<T>, Error> {
+    resolve_trait_instance::<T>(service_provider, ServiceKey::of::<T>())
+}
+
+/// Get an instance of the specified trait registered under the key.
+/// Initialize new object depending on the lifetime.
+pub fn get_keyed_trait_instance<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> Result<Arc<T>, Error> {
+    resolve_trait_instance::<T>(service_provider, ServiceKey::named::<T>(key))
+}
+
+/// Get an instance of the specified type, or `None` if it is not registered.
+pub fn try_get_instance<T: Send + Sync + Service + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> Result<Option<Arc<T>>, Error> {
+    try_resolve(
+        service_provider,
+        ServiceKey::of::<T>(),
+        resolve_instance::<T>,
+    )
+}
+
+/// Get an instance of the specified type registered under the key, or `None` if it is not registered.
+pub fn try_get_keyed_instance<T: Send + Sync + Service + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> Result<Option<Arc<T>>, Error> {
+    try_resolve(
+        service_provider,
+        ServiceKey::named::<T>(key),
+        resolve_instance::<T>,
+    )
+}
+
+/// Get an instance of the specified trait, or `None` if it is not registered.
+pub fn try_get_trait_instance<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> Result<Option<Arc<T>>, Error> {
+    try_resolve(
+        service_provider,
+        ServiceKey::of::<T>(),
+        resolve_trait_instance::<T>,
+    )
+}
+
+/// Get an instance of the specified trait registered under the key, or `None` if it is not registered.
+pub fn try_get_keyed_trait_instance<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: &str,
+) -> Result<Option<Arc<T>>, Error> {
+    try_resolve(
+        service_provider,
+        ServiceKey::named::<T>(key),
+        resolve_trait_instance::<T>,
+    )
+}
+
+/// Get the instances of all the implementations of the specified trait in registration order.
+/// Initialize new objects depending on their lifetime.
+pub fn get_all_trait_instances<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+) -> Result<Vec<Arc<T>>, Error> {
+    let key = ServiceKey::of::<T>();
+
+    service_provider
+        .get_service_definitions(&key)
+        .iter()
+        .map(|service_definition| {
+            resolve_trait_definition::<T>(service_provider, service_definition)
+                .map_err(|error| resolution_chain::with_context(error, &key))
+        })
+        .collect()
+}
+
+/// Resolve the service key if it is registered.
+fn try_resolve<T: ?Sized>(
+    service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
+    resolve: fn(&dyn ServiceProvider, ServiceKey) -> Result<Arc<T>, Error>,
+) -> Result<Option<Arc<T>>, Error> {
+    if service_provider.get_service_definition(&key).is_none() {
+        return Ok(None);
+    }
+
+    resolve(service_provider, key).map(Some)
+}
+
+/// Get or create the instance of the type registered under the service key.
+fn resolve_instance<T: Send + Sync + Service + 'static>(
+    service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
+) -> Result<Arc<T>, Error> {
+    let service = service_provider
+        .get_or_create_instance(key.clone())
+        .map_err(|error| resolution_chain::with_context(error, &key));
 
     match service {
         Ok(srv) => {
             // Return the created service
             match Arc::downcast::<T>(srv.as_any()) {
                 Ok(obj) => Ok(obj),
-                Err(_) => Err(Error::Internal("Cannot downcast service".to_string())),
+                Err(_) => Err(Error::DowncastFailed {
+                    type_name: key.to_string(),
+                }),
             }
         }
         Err(error) => Err(error),
     }
 }
 
-/// Get an instance of the specified trait.
-/// Initialize new object depending on the lifetime.
-pub fn get_trait_instance<T: ?Sized + Send + Sync + 'static>(
+/// Get or create the instance of the trait registered under the service key.
+fn resolve_trait_instance<T: ?Sized + Send + Sync + 'static>(
     service_provider: &dyn ServiceProvider,
+    key: ServiceKey,
 ) -> Result<Arc<T>, Error> {
-    let trait_name = std::any::type_name::<T>();
-    let service_name = service_provider
-        .get_service_container()
-        .trait_service_map
-        .get(trait_name);
-
-    return match service_name {
-        Some(type_name) => {
-            // Get or create service
-            let service = service_provider.get_or_create_instance(trait_name.to_string());
-
-            // Get service resolver
-            let service_resolver = type_name
-                .downcast_ref::<ServiceResolver<T>>()
-                .expect("Cannot get service resolver");
-
-            Ok((service_resolver.as_interface)(service.unwrap().as_any()))
+    match service_provider.get_service_definition(&key) {
+        Some(service_definition) => {
+            resolve_trait_definition::<T>(service_provider, service_definition)
         }
-        None => Err(Error::Internal("Cannot downcast service".to_string())),
-    };
+        None => Err(Error::NotRegistered {
+            type_name: key.to_string(),
+        }),
+    }
+    .map_err(|error| resolution_chain::with_context(error, &key))
+}
+
+/// Get or create the instance of a trait registration.
+pub(crate) fn resolve_trait_definition<T: ?Sized + Send + Sync + 'static>(
+    service_provider: &dyn ServiceProvider,
+    service_definition: &ServiceDefinition,
+) -> Result<Arc<T>, Error> {
+    // Get service resolver
+    let service_resolver = service_definition
+        .resolver
+        .as_ref()
+        .and_then(|resolver| resolver.downcast_ref::<ServiceResolver<T>>())
+        .ok_or_else(|| Error::DowncastFailed {
+            type_name: std::any::type_name::<T>().to_string(),
+        })?;
+
+    // Get or create service
+    let service = service_provider.get_or_create_instance_from_definition(service_definition)?;
+
+    Ok((service_resolver.as_interface)(service.as_any()))
 }
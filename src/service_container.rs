@@ -1,30 +1,208 @@
-use std::{any::Any, collections::HashMap, sync::Arc};
+use std::{
+    any::{TypeId, type_name},
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
+#[cfg(feature = "async")]
+use crate::async_service::{AsyncService, async_init};
+#[cfg(feature = "host")]
+use crate::host::HostedService;
 use crate::{
+    condition::Condition,
+    decorator::{Decorator, decorate_definition},
+    error::Error,
+    options::{Options, Validate, validate_options},
+    registration_policy::RegistrationPolicy,
     root_service_provider::RootServiceProvider,
     service::{Service, ServiceProvider, ServiceResolver},
-    service_definition::ServiceDefinition,
+    service_definition::{ServiceDefinition, ServiceInit},
+    service_dependency::ServiceDependency,
+    service_graph::ServiceGraph,
+    service_key::ServiceKey,
     service_lifetime::ServiceLifetime,
+    service_module::ServiceModule,
+    validation::{ValidationError, validate_service_container},
 };
 
+/// Number of service containers created, used to identify the registrations of each container.
+static CONTAINER_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Registrations by key, in registration order.
+pub(crate) type ServiceCollection = HashMap<ServiceKey, Vec<ServiceDefinition>>;
+
 pub struct ServiceContainer {
-    service_collection: HashMap<String, ServiceDefinition>,
-    pub unmanaged_services: HashMap<String, Arc<dyn Service>>,
-    pub trait_service_map: HashMap<String, Arc<dyn Any + Send + Sync + 'static>>,
+    id: usize,
+    service_collection: ServiceCollection,
+    registration_count: usize,
+    open_generic_services: HashMap<ServiceKey, ServiceLifetime>,
+    registration_policy: RegistrationPolicy,
+    /// Duplicate registrations with the policy applied when they were declared.
+    duplicate_registrations: Vec<(ValidationError, RegistrationPolicy)>,
+    installed_modules: HashSet<TypeId>,
+    /// Module declaring each registration identifier.
+    module_registrations: HashMap<usize, TypeId>,
+    installing_modules: Vec<(TypeId, &'static str)>,
+    /// Circular module dependencies found while installing the modules.
+    module_cycles: Vec<ValidationError>,
+    profiles: HashSet<String>,
+    pub unmanaged_services: HashMap<usize, Arc<dyn Service>>,
+}
+
+/// A clone has its own identifier, the registrations copied from the original container keep theirs.
+/// A child provider built from the clone shares the copied singletons with the provider of the original,
+/// except the ones of the registrations decorated after the clone.
+impl Clone for ServiceContainer {
+    fn clone(&self) -> Self {
+        Self {
+            id: CONTAINER_COUNT.fetch_add(1, Ordering::Relaxed),
+            ..self.snapshot()
+        }
+    }
+}
+
+impl Default for ServiceContainer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ServiceContainer {
     pub fn new() -> Self {
         Self {
+            id: CONTAINER_COUNT.fetch_add(1, Ordering::Relaxed),
             service_collection: HashMap::new(),
+            registration_count: 0,
+            open_generic_services: HashMap::new(),
+            registration_policy: RegistrationPolicy::default(),
+            duplicate_registrations: Vec::new(),
+            installed_modules: HashSet::new(),
+            module_registrations: HashMap::new(),
+            installing_modules: Vec::new(),
+            module_cycles: Vec::new(),
+            profiles: HashSet::new(),
             unmanaged_services: HashMap::new(),
-            trait_service_map: HashMap::new(),
+        }
+    }
+
+    /// Create a service container with the services declared by the `#[shive::service]` attribute.
+    #[cfg(feature = "discovery")]
+    pub fn from_discovered() -> Self {
+        let mut service_container = Self::new();
+        service_container.add_discovered();
+
+        service_container
+    }
+
+    /// Declare the services of the `#[shive::service]` attribute, ordered by type name.
+    #[cfg(feature = "discovery")]
+    pub fn add_discovered(&mut self) {
+        let mut discovered_services: Vec<_> =
+            crate::discovery::DISCOVERED_SERVICES.iter().collect();
+        discovered_services.sort_by_key(|discovered_service| discovered_service.type_name);
+
+        for discovered_service in discovered_services {
+            (discovered_service.register)(self);
+        }
+    }
+
+    /// Create a service container with an active profile.
+    pub fn with_profile(profile: &str) -> Self {
+        let mut service_container = Self::new();
+        service_container.add_profile(profile);
+
+        service_container
+    }
+
+    /// Activate a profile used by the conditional registrations.
+    pub fn add_profile(&mut self, profile: &str) {
+        self.profiles.insert(profile.to_string());
+    }
+
+    /// Check if the profile is active in the service container.
+    pub fn is_profile_active(&self, profile: &str) -> bool {
+        self.profiles.contains(profile)
+    }
+
+    /// Declare a singleton enabled when the condition is met.
+    pub fn add_singleton_if<T: Service + 'static>(&mut self, condition: Condition) {
+        self.add_conditional(condition, Self::add_singleton::<T>);
+    }
+
+    /// Declare a trait singleton enabled when the condition is met.
+    pub fn add_trait_singleton_if<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        condition: Condition,
+    ) {
+        self.add_conditional(condition, |service_container| {
+            service_container.add_trait_singleton::<I, T>(resolver)
+        });
+    }
+
+    /// Declare a scoped service enabled when the condition is met.
+    pub fn add_scoped_if<T: Service + 'static>(&mut self, condition: Condition) {
+        self.add_conditional(condition, Self::add_scoped::<T>);
+    }
+
+    /// Declare a trait scoped service enabled when the condition is met.
+    pub fn add_trait_scoped_if<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        condition: Condition,
+    ) {
+        self.add_conditional(condition, |service_container| {
+            service_container.add_trait_scoped::<I, T>(resolver)
+        });
+    }
+
+    /// Declare a transient service enabled when the condition is met.
+    pub fn add_transient_if<T: Service + 'static>(&mut self, condition: Condition) {
+        self.add_conditional(condition, Self::add_transient::<T>);
+    }
+
+    /// Declare a trait transient service enabled when the condition is met.
+    pub fn add_trait_transient_if<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        condition: Condition,
+    ) {
+        self.add_conditional(condition, |service_container| {
+            service_container.add_trait_transient::<I, T>(resolver)
+        });
+    }
+
+    /// Run the declarations and enable them when the condition is met.
+    pub fn add_conditional(&mut self, condition: Condition, add: impl FnOnce(&mut Self)) {
+        let first_id = self.registration_count;
+
+        add(self);
+
+        for service_definition in self
+            .service_collection
+            .values_mut()
+            .flatten()
+            .filter(|service_definition| service_definition.id >= first_id)
+        {
+            service_definition.condition = Some(match service_definition.condition.take() {
+                Some(inner) => condition.clone().and(inner),
+                None => condition.clone(),
+            });
         }
     }
 
     /// Declare and create a singleton in the service container.
     pub fn add_singleton<T: Service + 'static>(&mut self) {
-        self.add_service::<T>(ServiceLifetime::Singleton, None);
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Singleton,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+        );
     }
 
     /// Declare and create a singleton in the service container.
@@ -32,12 +210,25 @@ impl ServiceContainer {
         &mut self,
         resolver: ServiceResolver<I>,
     ) {
-        self.add_trait_service::<I, T>(ServiceLifetime::Singleton, None, resolver);
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Singleton,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+            resolver,
+        );
     }
 
     /// Declare and create a scoped instance in the service container.
     pub fn add_scoped<T: Service + 'static>(&mut self) {
-        self.add_service::<T>(ServiceLifetime::Scoped, None);
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Scoped,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+        );
     }
 
     /// Declare and create a scoped in the service container.
@@ -45,12 +236,25 @@ impl ServiceContainer {
         &mut self,
         resolver: ServiceResolver<I>,
     ) {
-        self.add_trait_service::<I, T>(ServiceLifetime::Scoped, None, resolver);
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Scoped,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+            resolver,
+        );
     }
 
     /// Declare and create a transient instance in the service container.
     pub fn add_transient<T: Service + 'static>(&mut self) {
-        self.add_service::<T>(ServiceLifetime::Transient, None);
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Transient,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+        );
     }
 
     /// Declare and create a transient in the service container.
@@ -58,12 +262,25 @@ impl ServiceContainer {
         &mut self,
         resolver: ServiceResolver<I>,
     ) {
-        self.add_trait_service::<I, T>(ServiceLifetime::Transient, None, resolver);
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Transient,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+            resolver,
+        );
     }
 
     /// Declare and create an unmanaged instance in the service container.
     pub fn add_unmanaged<T: Service + 'static>(&mut self, instance: T) {
-        self.add_service::<T>(ServiceLifetime::Unmanaged, Some(instance));
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Unmanaged,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            Some(instance),
+        );
     }
 
     /// Declare and create an unmanaged instance in the service container.
@@ -72,73 +289,1093 @@ impl ServiceContainer {
         resolver: ServiceResolver<I>,
         instance: T,
     ) {
-        self.add_trait_service::<I, T>(ServiceLifetime::Unmanaged, Some(instance), resolver);
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Unmanaged,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            Some(instance),
+            resolver,
+        );
+    }
+
+    /// Declare a singleton created by the factory in the service container.
+    pub fn add_singleton_with<T, F>(&mut self, factory: F)
+    where
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+    {
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Singleton,
+            factory_init(factory),
+            Vec::new(),
+            None,
+        );
+    }
+
+    /// Declare a trait singleton created by the factory in the service container.
+    pub fn add_trait_singleton_with<I, T, F>(&mut self, resolver: ServiceResolver<I>, factory: F)
+    where
+        I: ?Sized + Send + Sync + 'static,
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+    {
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Singleton,
+            factory_init(factory),
+            Vec::new(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a scoped service created by the factory in the service container.
+    pub fn add_scoped_with<T, F>(&mut self, factory: F)
+    where
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+    {
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Scoped,
+            factory_init(factory),
+            Vec::new(),
+            None,
+        );
+    }
+
+    /// Declare a trait scoped service created by the factory in the service container.
+    pub fn add_trait_scoped_with<I, T, F>(&mut self, resolver: ServiceResolver<I>, factory: F)
+    where
+        I: ?Sized + Send + Sync + 'static,
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+    {
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Scoped,
+            factory_init(factory),
+            Vec::new(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a transient service created by the factory in the service container.
+    pub fn add_transient_with<T, F>(&mut self, factory: F)
+    where
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+    {
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Transient,
+            factory_init(factory),
+            Vec::new(),
+            None,
+        );
+    }
+
+    /// Declare a trait transient service created by the factory in the service container.
+    pub fn add_trait_transient_with<I, T, F>(&mut self, resolver: ServiceResolver<I>, factory: F)
+    where
+        I: ?Sized + Send + Sync + 'static,
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+    {
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Transient,
+            factory_init(factory),
+            Vec::new(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a singleton created by a fallible factory in the service container,
+    /// the error of the factory is returned as `Error::FactoryFailed`.
+    pub fn add_singleton_try_with<T, F, E>(&mut self, factory: F)
+    where
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Singleton,
+            try_factory_init(factory),
+            Vec::new(),
+            None,
+        );
+    }
+
+    /// Declare a trait singleton created by a fallible factory in the service container.
+    pub fn add_trait_singleton_try_with<I, T, F, E>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        factory: F,
+    ) where
+        I: ?Sized + Send + Sync + 'static,
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Singleton,
+            try_factory_init(factory),
+            Vec::new(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a scoped service created by a fallible factory in the service container,
+    /// the error of the factory is returned as `Error::FactoryFailed`.
+    pub fn add_scoped_try_with<T, F, E>(&mut self, factory: F)
+    where
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Scoped,
+            try_factory_init(factory),
+            Vec::new(),
+            None,
+        );
+    }
+
+    /// Declare a trait scoped service created by a fallible factory in the service container.
+    pub fn add_trait_scoped_try_with<I, T, F, E>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        factory: F,
+    ) where
+        I: ?Sized + Send + Sync + 'static,
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Scoped,
+            try_factory_init(factory),
+            Vec::new(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a transient service created by a fallible factory in the service container,
+    /// the error of the factory is returned as `Error::FactoryFailed`.
+    pub fn add_transient_try_with<T, F, E>(&mut self, factory: F)
+    where
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.add_service::<T>(
+            ServiceKey::of::<T>(),
+            ServiceLifetime::Transient,
+            try_factory_init(factory),
+            Vec::new(),
+            None,
+        );
+    }
+
+    /// Declare a trait transient service created by a fallible factory in the service container.
+    pub fn add_trait_transient_try_with<I, T, F, E>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        factory: F,
+    ) where
+        I: ?Sized + Send + Sync + 'static,
+        T: Service + 'static,
+        F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.add_trait_service::<I, T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Transient,
+            try_factory_init(factory),
+            Vec::new(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a keyed singleton in the service container.
+    pub fn add_keyed_singleton<T: Service + 'static>(&mut self, key: &str) {
+        self.add_service::<T>(
+            ServiceKey::named::<T>(key),
+            ServiceLifetime::Singleton,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+        );
+    }
+
+    /// Declare a keyed trait singleton in the service container.
+    pub fn add_keyed_trait_singleton<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        key: &str,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.add_trait_service::<I, T>(
+            ServiceKey::named::<I>(key),
+            ServiceLifetime::Singleton,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a keyed scoped service in the service container.
+    pub fn add_keyed_scoped<T: Service + 'static>(&mut self, key: &str) {
+        self.add_service::<T>(
+            ServiceKey::named::<T>(key),
+            ServiceLifetime::Scoped,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+        );
+    }
+
+    /// Declare a keyed trait scoped service in the service container.
+    pub fn add_keyed_trait_scoped<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        key: &str,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.add_trait_service::<I, T>(
+            ServiceKey::named::<I>(key),
+            ServiceLifetime::Scoped,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a keyed transient service in the service container.
+    pub fn add_keyed_transient<T: Service + 'static>(&mut self, key: &str) {
+        self.add_service::<T>(
+            ServiceKey::named::<T>(key),
+            ServiceLifetime::Transient,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+        );
+    }
+
+    /// Declare a keyed trait transient service in the service container.
+    pub fn add_keyed_trait_transient<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        key: &str,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.add_trait_service::<I, T>(
+            ServiceKey::named::<I>(key),
+            ServiceLifetime::Transient,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            None,
+            resolver,
+        );
+    }
+
+    /// Declare a keyed unmanaged instance in the service container.
+    pub fn add_keyed_unmanaged<T: Service + 'static>(&mut self, key: &str, instance: T) {
+        self.add_service::<T>(
+            ServiceKey::named::<T>(key),
+            ServiceLifetime::Unmanaged,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            Some(instance),
+        );
+    }
+
+    /// Declare a keyed trait unmanaged instance in the service container.
+    pub fn add_keyed_trait_unmanaged<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        key: &str,
+        resolver: ServiceResolver<I>,
+        instance: T,
+    ) {
+        self.add_trait_service::<I, T>(
+            ServiceKey::named::<I>(key),
+            ServiceLifetime::Unmanaged,
+            Arc::new(T::try_init),
+            T::dependencies(),
+            Some(instance),
+            resolver,
+        );
+    }
+
+    /// Declare an asynchronous singleton in the service container.
+    #[cfg(feature = "async")]
+    pub fn add_async_singleton<T: AsyncService>(&mut self) {
+        self.add_async_service::<T>(ServiceKey::of::<T>(), ServiceLifetime::Singleton, None);
+    }
+
+    /// Declare an asynchronous trait singleton in the service container.
+    #[cfg(feature = "async")]
+    pub fn add_async_trait_singleton<I: ?Sized + Send + Sync + 'static, T: AsyncService>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.add_async_service::<T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Singleton,
+            Some(Arc::new(resolver)),
+        );
+    }
+
+    /// Declare an asynchronous scoped service in the service container.
+    #[cfg(feature = "async")]
+    pub fn add_async_scoped<T: AsyncService>(&mut self) {
+        self.add_async_service::<T>(ServiceKey::of::<T>(), ServiceLifetime::Scoped, None);
+    }
+
+    /// Declare an asynchronous trait scoped service in the service container.
+    #[cfg(feature = "async")]
+    pub fn add_async_trait_scoped<I: ?Sized + Send + Sync + 'static, T: AsyncService>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.add_async_service::<T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Scoped,
+            Some(Arc::new(resolver)),
+        );
+    }
+
+    /// Declare an asynchronous transient service in the service container.
+    #[cfg(feature = "async")]
+    pub fn add_async_transient<T: AsyncService>(&mut self) {
+        self.add_async_service::<T>(ServiceKey::of::<T>(), ServiceLifetime::Transient, None);
+    }
+
+    /// Declare an asynchronous trait transient service in the service container.
+    #[cfg(feature = "async")]
+    pub fn add_async_trait_transient<I: ?Sized + Send + Sync + 'static, T: AsyncService>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.add_async_service::<T>(
+            ServiceKey::of::<I>(),
+            ServiceLifetime::Transient,
+            Some(Arc::new(resolver)),
+        );
+    }
+
+    /// Declare a hosted service, created as a singleton and started by the host.
+    #[cfg(feature = "host")]
+    pub fn add_hosted_service<T: Service + HostedService>(&mut self) {
+        self.add_trait_singleton::<dyn HostedService, T>(ServiceResolver {
+            as_interface: |service| service.downcast::<T>().unwrap(),
+        });
+    }
+
+    /// Declare an asynchronous hosted service, created as a singleton and started by the host.
+    #[cfg(feature = "host")]
+    pub fn add_async_hosted_service<T: AsyncService + HostedService>(&mut self) {
+        self.add_async_trait_singleton::<dyn HostedService, T>(ServiceResolver {
+            as_interface: |service| service.downcast::<T>().unwrap(),
+        });
+    }
+
+    /// Declare an open generic singleton in the service container.
+    /// `D` is the `Definition` type of the `GenericService` instantiations.
+    pub fn add_open_singleton<D: ?Sized + 'static>(&mut self) {
+        self.add_open_generic_service::<D>(ServiceLifetime::Singleton);
+    }
+
+    /// Declare an open generic scoped service in the service container.
+    /// `D` is the `Definition` type of the `GenericService` instantiations.
+    pub fn add_open_scoped<D: ?Sized + 'static>(&mut self) {
+        self.add_open_generic_service::<D>(ServiceLifetime::Scoped);
+    }
+
+    /// Declare an open generic transient service in the service container.
+    /// `D` is the `Definition` type of the `GenericService` instantiations.
+    pub fn add_open_transient<D: ?Sized + 'static>(&mut self) {
+        self.add_open_generic_service::<D>(ServiceLifetime::Transient);
+    }
+
+    /// Declare a singleton in the service container if the type is not declared yet.
+    pub fn try_add_singleton<T: Service + 'static>(&mut self) -> bool {
+        self.try_add(ServiceKey::of::<T>(), |container| {
+            container.add_singleton::<T>()
+        })
+    }
+
+    /// Declare a trait singleton in the service container if the trait is not declared yet.
+    pub fn try_add_trait_singleton<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) -> bool {
+        self.try_add(ServiceKey::of::<I>(), |container| {
+            container.add_trait_singleton::<I, T>(resolver)
+        })
+    }
+
+    /// Replace the declarations of the type by a singleton.
+    pub fn replace_singleton<T: Service + 'static>(&mut self) {
+        self.remove::<T>();
+        self.add_singleton::<T>();
+    }
+
+    /// Replace the declarations of the trait by a singleton.
+    pub fn replace_trait_singleton<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.remove::<I>();
+        self.add_trait_singleton::<I, T>(resolver);
+    }
+
+    /// Declare a scoped service in the service container if the type is not declared yet.
+    pub fn try_add_scoped<T: Service + 'static>(&mut self) -> bool {
+        self.try_add(ServiceKey::of::<T>(), |container| {
+            container.add_scoped::<T>()
+        })
+    }
+
+    /// Declare a trait scoped service in the service container if the trait is not declared yet.
+    pub fn try_add_trait_scoped<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) -> bool {
+        self.try_add(ServiceKey::of::<I>(), |container| {
+            container.add_trait_scoped::<I, T>(resolver)
+        })
+    }
+
+    /// Replace the declarations of the type by a scoped service.
+    pub fn replace_scoped<T: Service + 'static>(&mut self) {
+        self.remove::<T>();
+        self.add_scoped::<T>();
+    }
+
+    /// Replace the declarations of the trait by a scoped service.
+    pub fn replace_trait_scoped<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.remove::<I>();
+        self.add_trait_scoped::<I, T>(resolver);
+    }
+
+    /// Declare a transient service in the service container if the type is not declared yet.
+    pub fn try_add_transient<T: Service + 'static>(&mut self) -> bool {
+        self.try_add(ServiceKey::of::<T>(), |container| {
+            container.add_transient::<T>()
+        })
+    }
+
+    /// Declare a trait transient service in the service container if the trait is not declared yet.
+    pub fn try_add_trait_transient<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) -> bool {
+        self.try_add(ServiceKey::of::<I>(), |container| {
+            container.add_trait_transient::<I, T>(resolver)
+        })
+    }
+
+    /// Replace the declarations of the type by a transient service.
+    pub fn replace_transient<T: Service + 'static>(&mut self) {
+        self.remove::<T>();
+        self.add_transient::<T>();
+    }
+
+    /// Replace the declarations of the trait by a transient service.
+    pub fn replace_trait_transient<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+    ) {
+        self.remove::<I>();
+        self.add_trait_transient::<I, T>(resolver);
+    }
+
+    /// Declare an unmanaged instance in the service container if the type is not declared yet.
+    pub fn try_add_unmanaged<T: Service + 'static>(&mut self, instance: T) -> bool {
+        self.try_add(ServiceKey::of::<T>(), |container| {
+            container.add_unmanaged::<T>(instance)
+        })
+    }
+
+    /// Declare a trait unmanaged instance in the service container if the trait is not declared yet.
+    pub fn try_add_trait_unmanaged<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        instance: T,
+    ) -> bool {
+        self.try_add(ServiceKey::of::<I>(), |container| {
+            container.add_trait_unmanaged::<I, T>(resolver, instance)
+        })
+    }
+
+    /// Replace the declarations of the type by an unmanaged instance.
+    pub fn replace_unmanaged<T: Service + 'static>(&mut self, instance: T) {
+        self.remove::<T>();
+        self.add_unmanaged::<T>(instance);
+    }
+
+    /// Replace the declarations of the trait by an unmanaged instance.
+    pub fn replace_trait_unmanaged<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
+        &mut self,
+        resolver: ServiceResolver<I>,
+        instance: T,
+    ) {
+        self.remove::<I>();
+        self.add_trait_unmanaged::<I, T>(resolver, instance);
+    }
+
+    /// Declare the options of a configuration, checked when the service container is validated.
+    pub fn add_options<T: Validate + Send + Sync + 'static>(&mut self, value: T) {
+        self.add_options_definition(ServiceKey::of::<Options<T>>(), value);
+    }
+
+    /// Declare the options of a configuration under the name.
+    pub fn add_named_options<T: Validate + Send + Sync + 'static>(&mut self, name: &str, value: T) {
+        self.add_options_definition(ServiceKey::named::<Options<T>>(name), value);
+    }
+
+    /// Remove all the declarations of the type or trait from the service container.
+    /// The keyed declarations are kept, they are removed with `remove_keyed`.
+    /// Return `true` if a declaration was removed.
+    pub fn remove<T: ?Sized + 'static>(&mut self) -> bool {
+        self.remove_key(&ServiceKey::of::<T>())
+    }
+
+    /// Remove all the declarations of the type or trait registered under the key.
+    /// Return `true` if a declaration was removed.
+    pub fn remove_keyed<T: ?Sized + 'static>(&mut self, key: &str) -> bool {
+        self.remove_key(&ServiceKey::named::<T>(key))
+    }
+
+    /// Remove the declarations of the key and their duplicates.
+    fn remove_key(&mut self, key: &ServiceKey) -> bool {
+        self.duplicate_registrations.retain(|(duplicate, _)| {
+            !matches!(duplicate, ValidationError::DuplicateRegistration { service, .. } if service == key)
+        });
+
+        match self.service_collection.remove(key) {
+            Some(service_definitions) => {
+                for service_definition in service_definitions {
+                    self.unmanaged_services.remove(&service_definition.id);
+                }
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check if the type or trait is declared in the service container.
+    pub fn contains<T: ?Sized + 'static>(&self) -> bool {
+        self.service_collection.contains_key(&ServiceKey::of::<T>())
+    }
+
+    /// Set the behavior of the service container on duplicate declarations.
+    pub fn set_registration_policy(&mut self, registration_policy: RegistrationPolicy) {
+        self.registration_policy = registration_policy;
+    }
+
+    /// Run the declaration if the key is not declared yet.
+    fn try_add(&mut self, key: ServiceKey, add: impl FnOnce(&mut Self)) -> bool {
+        if self.service_collection.contains_key(&key) {
+            return false;
+        }
+
+        add(self);
+
+        true
+    }
+
+    /// Install the module and its dependencies in the service container.
+    /// A module is installed once, after the modules it depends on.
+    /// A circular module dependency is skipped and reported by `validate`.
+    pub fn add_module<M: ServiceModule>(&mut self) {
+        let type_id = TypeId::of::<M>();
+
+        if self.installed_modules.contains(&type_id) {
+            return;
+        }
+
+        if let Some(position) = self
+            .installing_modules
+            .iter()
+            .position(|(installing, _)| *installing == type_id)
+        {
+            let path = self.installing_modules[position..]
+                .iter()
+                .map(|(_, name)| name.to_string())
+                .chain([type_name::<M>().to_string()])
+                .collect();
+            self.module_cycles
+                .push(ValidationError::CircularModuleDependency { path });
+            return;
+        }
+
+        self.installing_modules.push((type_id, type_name::<M>()));
+        for module_dependency in M::dependencies() {
+            (module_dependency.install)(self);
+        }
+        self.installing_modules.pop();
+
+        // The registrations of the modules installed by `register` belong to them
+        let first_id = self.registration_count;
+        M::register(self);
+        for id in first_id..self.registration_count {
+            self.module_registrations.entry(id).or_insert(type_id);
+        }
+        self.installed_modules.insert(type_id);
+    }
+
+    /// Check if the module is installed in the service container.
+    pub fn contains_module<M: ServiceModule>(&self) -> bool {
+        self.installed_modules.contains(&TypeId::of::<M>())
+    }
+
+    /// Move the registrations, open generic services and modules of another service container.
+    /// The merged registrations are declared after the existing ones, following the registration policy.
+    /// The registrations of the modules already installed in this container are skipped.
+    pub fn merge(&mut self, mut other: ServiceContainer) {
+        let mut service_definitions: Vec<_> = other
+            .service_collection
+            .drain()
+            .flat_map(|(key, service_definitions)| {
+                service_definitions
+                    .into_iter()
+                    .map(move |service_definition| (key.clone(), service_definition))
+            })
+            .collect();
+        service_definitions.sort_by_key(|(_, service_definition)| service_definition.id);
+
+        for (key, mut service_definition) in service_definitions {
+            let module = other
+                .module_registrations
+                .get(&service_definition.id)
+                .copied();
+            if module.is_some_and(|module| self.installed_modules.contains(&module)) {
+                continue;
+            }
+
+            let instance = other.unmanaged_services.remove(&service_definition.id);
+            service_definition.id = self.next_registration_id();
+            if let Some(module) = module {
+                self.module_registrations
+                    .insert(service_definition.id, module);
+            }
+            service_definition.container_id = self.id;
+            self.add_service_definition(key, service_definition, instance);
+        }
+
+        self.open_generic_services
+            .extend(other.open_generic_services);
+        self.installed_modules.extend(other.installed_modules);
+        self.module_cycles.extend(other.module_cycles);
+    }
+
+    /// Decorate the registrations of the trait declared so far with `D`.
+    /// Decorators stack in declaration order and keep the lifetime of the decorated service.
+    /// The registrations copied from another container get their own identifier,
+    /// their instances are not shared with the provider of the other container anymore.
+    pub fn decorate<I: ?Sized + Send + Sync + 'static, D: Decorator<I>>(&mut self) {
+        let key = ServiceKey::of::<I>();
+
+        if let Some(mut service_definitions) = self.service_collection.remove(&key) {
+            for service_definition in service_definitions.iter_mut() {
+                let unmanaged_instance = self.unmanaged_services.remove(&service_definition.id);
+
+                if service_definition.container_id != self.id {
+                    let id = self.next_registration_id();
+                    if let Some(module) = self.module_registrations.remove(&service_definition.id) {
+                        self.module_registrations.insert(id, module);
+                    }
+                    service_definition.id = id;
+                    service_definition.container_id = self.id;
+                }
+
+                decorate_definition::<I, D>(service_definition, unmanaged_instance);
+            }
+
+            self.service_collection.insert(key, service_definitions);
+        }
+    }
+
+    /// Add an open generic service with its lifetime
+    fn add_open_generic_service<D: ?Sized + 'static>(&mut self, lifetime: ServiceLifetime) {
+        self.open_generic_services
+            .insert(ServiceKey::of::<D>(), lifetime);
     }
 
-    /// Add a trait service with its lifetime and instance
+    /// Add a trait service with its lifetime, the dependencies resolved by its init function and its instance
     fn add_trait_service<I: ?Sized + Send + Sync + 'static, T: Service + 'static>(
         &mut self,
+        key: ServiceKey,
         lifetime: ServiceLifetime,
+        service_init: ServiceInit,
+        dependencies: Vec<ServiceDependency>,
         instance: Option<T>,
         resolver: ServiceResolver<I>,
     ) {
-        let trait_name = std::any::type_name::<I>().to_string();
-        let service_init: Arc<fn(&dyn ServiceProvider) -> Arc<dyn Service>> = Arc::new(T::init);
-        let service_instance: Option<Arc<dyn Service>> = match instance {
-            Some(service) => Some(Arc::new(service)),
-            None => None,
+        let service_definition = ServiceDefinition {
+            id: self.next_registration_id(),
+            container_id: self.id,
+            implementation: ServiceKey::of::<T>(),
+            lifetime,
+            init: service_init,
+            async_init: None,
+            resolver: Some(Arc::new(resolver)),
+            dependencies,
+            condition: None,
+            validator: None,
         };
+        let service_instance = instance.map(|service| Arc::new(service) as Arc<dyn Service>);
 
-        // Add the service
-        self.add_keyed_service(&trait_name, lifetime, service_init, service_instance);
-
-        // Add the resolver
-        self.trait_service_map
-            .insert(trait_name, Arc::new(resolver));
+        self.add_service_definition(key, service_definition, service_instance);
     }
 
-    /// Add a service with its lifetime and instance
+    /// Add a service with its lifetime, the dependencies resolved by its init function and its instance
     fn add_service<T: Service + 'static>(
         &mut self,
+        key: ServiceKey,
         lifetime: ServiceLifetime,
+        service_init: ServiceInit,
+        dependencies: Vec<ServiceDependency>,
         instance: Option<T>,
     ) {
-        let service_init: Arc<fn(&dyn ServiceProvider) -> Arc<dyn Service>> = Arc::new(T::init);
-        let type_name = std::any::type_name::<T>().to_string();
-        let service_instance: Option<Arc<dyn Service>> = match instance {
-            Some(service) => Some(Arc::new(service)),
-            None => None,
+        let service_definition = ServiceDefinition {
+            id: self.next_registration_id(),
+            container_id: self.id,
+            implementation: ServiceKey::of::<T>(),
+            lifetime,
+            init: service_init,
+            async_init: None,
+            resolver: None,
+            dependencies,
+            condition: None,
+            validator: None,
         };
+        let service_instance = instance.map(|service| Arc::new(service) as Arc<dyn Service>);
 
-        self.add_keyed_service(&type_name, lifetime, service_init, service_instance);
+        self.add_service_definition(key, service_definition, service_instance);
     }
 
-    /// Add a service with its lifetime and instance
-    fn add_keyed_service(
+    /// Add an asynchronous service with its lifetime
+    #[cfg(feature = "async")]
+    fn add_async_service<T: AsyncService>(
         &mut self,
-        key: &str,
+        key: ServiceKey,
         lifetime: ServiceLifetime,
-        init: Arc<fn(&dyn ServiceProvider) -> Arc<dyn Service>>,
+        resolver: Option<Arc<dyn std::any::Any + Send + Sync + 'static>>,
+    ) {
+        let service_definition = ServiceDefinition {
+            id: self.next_registration_id(),
+            container_id: self.id,
+            implementation: ServiceKey::of::<T>(),
+            lifetime,
+            init: Arc::new(<T as Service>::try_init),
+            async_init: Some(async_init::<T>()),
+            resolver,
+            dependencies: T::dependencies(),
+            condition: None,
+            validator: None,
+        };
+
+        self.add_service_definition(key, service_definition, None);
+    }
+
+    /// Add the options of a configuration with their validator
+    fn add_options_definition<T: Validate + Send + Sync + 'static>(
+        &mut self,
+        key: ServiceKey,
+        value: T,
+    ) {
+        let service_definition = ServiceDefinition {
+            id: self.next_registration_id(),
+            container_id: self.id,
+            implementation: ServiceKey::of::<Options<T>>(),
+            lifetime: ServiceLifetime::Unmanaged,
+            init: Arc::new(Options::<T>::try_init),
+            async_init: None,
+            resolver: None,
+            dependencies: Vec::new(),
+            condition: None,
+            validator: Some(validate_options::<T>),
+        };
+        let service_instance: Arc<dyn Service> = Arc::new(Options::new(value));
+
+        self.add_service_definition(key, service_definition, Some(service_instance));
+    }
+
+    /// Add a service definition and its unmanaged instance under the key.
+    /// The definitions of a key are kept in registration order.
+    fn add_service_definition(
+        &mut self,
+        key: ServiceKey,
+        service_definition: ServiceDefinition,
         instance: Option<Arc<dyn Service>>,
     ) {
-        let service_definition = ServiceDefinition { init, lifetime };
+        // Apply the registration policy on duplicate implementations
+        let implementation = &service_definition.implementation;
+        let is_duplicate = self
+            .get_service_definitions_from_key(&key)
+            .iter()
+            .any(|registered| registered.implementation == *implementation);
 
-        self.service_collection
-            .insert(key.to_string(), service_definition);
+        if is_duplicate && self.registration_policy != RegistrationPolicy::Allow {
+            self.duplicate_registrations.push((
+                ValidationError::DuplicateRegistration {
+                    service: key.clone(),
+                    implementation: implementation.clone(),
+                },
+                self.registration_policy,
+            ));
+        }
 
-        if matches!(lifetime, ServiceLifetime::Unmanaged) && instance.is_some() {
+        if let (ServiceLifetime::Unmanaged, Some(instance)) =
+            (service_definition.lifetime, instance)
+        {
             self.unmanaged_services
-                .insert(key.to_string(), instance.unwrap());
+                .insert(service_definition.id, instance);
         }
+
+        self.service_collection
+            .entry(key)
+            .or_default()
+            .push(service_definition);
+    }
+
+    /// Get a new registration identifier
+    fn next_registration_id(&mut self) -> usize {
+        let id = self.registration_count;
+        self.registration_count += 1;
+
+        id
+    }
+
+    /// Get the last registered ServiceDefinition of the key from the service container
+    pub fn get_service_definition_from_key(&self, key: &ServiceKey) -> Option<&ServiceDefinition> {
+        self.get_service_definitions_from_key(key).last()
+    }
+
+    /// Get all the ServiceDefinition of the key in registration order from the service container
+    pub fn get_service_definitions_from_key(&self, key: &ServiceKey) -> &[ServiceDefinition] {
+        self.service_collection
+            .get(key)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
     }
 
-    /// Get ServiceInstance from the service container
-    pub fn get_service_definition_from_key(&self, type_name: String) -> Option<&ServiceDefinition> {
-        self.service_collection.get(&type_name)
+    /// Get all the service definitions with their key in registration order
+    pub fn get_service_definitions(&self) -> Vec<(&ServiceKey, &ServiceDefinition)> {
+        sort_service_definitions(&self.service_collection)
+    }
+
+    /// Get the lifetime of an open generic registration from the service container
+    pub fn get_open_generic_lifetime(&self, key: &ServiceKey) -> Option<ServiceLifetime> {
+        self.open_generic_services.get(key).copied()
+    }
+
+    /// Get the registrations enabled by their condition, evaluated with the active profiles.
+    pub(crate) fn get_enabled_service_collection(&self) -> ServiceCollection {
+        let mut service_collection: ServiceCollection = self
+            .service_collection
+            .iter()
+            .map(|(key, service_definitions)| {
+                let enabled_definitions: Vec<_> = service_definitions
+                    .iter()
+                    .filter(|service_definition| {
+                        service_definition
+                            .condition
+                            .as_ref()
+                            .is_none_or(|condition| condition.is_met(&self.profiles))
+                    })
+                    .cloned()
+                    .collect();
+
+                (key.clone(), enabled_definitions)
+            })
+            .filter(|(_, service_definitions)| !service_definitions.is_empty())
+            .collect();
+
+        self.add_generic_instantiations(&mut service_collection);
+
+        service_collection
+    }
+
+    /// Add the closed instantiations declared by the dependencies on the open generic registrations,
+    /// after the registrations of the service container.
+    fn add_generic_instantiations(&self, service_collection: &mut ServiceCollection) {
+        let mut dependencies: Vec<ServiceDependency> = service_collection
+            .values()
+            .flatten()
+            .flat_map(|service_definition| service_definition.dependencies.iter().cloned())
+            .collect();
+        let mut id = self.registration_count;
+
+        while let Some(dependency) = dependencies.pop() {
+            let Some(instantiation) = dependency.instantiation else {
+                continue;
+            };
+            if service_collection.contains_key(&dependency.key) {
+                continue;
+            }
+            let Some(lifetime) = self.get_open_generic_lifetime(&instantiation.definition) else {
+                continue;
+            };
+
+            let instantiation_dependencies = (instantiation.dependencies)();
+            dependencies.extend(instantiation_dependencies.iter().cloned());
+            service_collection.insert(
+                dependency.key,
+                vec![ServiceDefinition {
+                    id,
+                    container_id: self.id,
+                    implementation: instantiation.implementation,
+                    lifetime,
+                    init: instantiation.init,
+                    async_init: None,
+                    resolver: instantiation.resolver,
+                    dependencies: instantiation_dependencies,
+                    condition: None,
+                    validator: None,
+                }],
+            );
+            id += 1;
+        }
+    }
+
+    /// Copy the service container with its identifier, used by the service provider built from it.
+    pub(crate) fn snapshot(&self) -> Self {
+        Self {
+            id: self.id,
+            service_collection: self.service_collection.clone(),
+            registration_count: self.registration_count,
+            open_generic_services: self.open_generic_services.clone(),
+            registration_policy: self.registration_policy,
+            duplicate_registrations: self.duplicate_registrations.clone(),
+            installed_modules: self.installed_modules.clone(),
+            module_registrations: self.module_registrations.clone(),
+            installing_modules: self.installing_modules.clone(),
+            module_cycles: self.module_cycles.clone(),
+            profiles: self.profiles.clone(),
+            unmanaged_services: self.unmanaged_services.clone(),
+        }
+    }
+
+    /// Get the identifier of the service container
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Get the number of registrations in the service container
+    pub(crate) fn registration_count(&self) -> usize {
+        self.registration_count
     }
 
     /// Get a root service provider to get the singleton and unmanaged services
     pub fn build(&self) -> RootServiceProvider {
         RootServiceProvider::new(self)
     }
+
+    /// Check the dependencies of all the registrations of the service container.
+    /// Report the missing services, the circular dependencies and the captive dependencies.
+    pub fn validate(&self) -> Result<(), Error> {
+        let validation_errors = validate_service_container(self);
+
+        if validation_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(validation_errors))
+        }
+    }
+
+    /// Get the duplicate registrations declared with the `Warn` policy.
+    pub fn warnings(&self) -> Vec<ValidationError> {
+        self.duplicate_registrations(RegistrationPolicy::Warn)
+    }
+
+    /// Get the circular module dependencies found while installing the modules.
+    pub(crate) fn module_cycles(&self) -> &[ValidationError] {
+        &self.module_cycles
+    }
+
+    /// Get the duplicate registrations declared with the policy.
+    pub(crate) fn duplicate_registrations(
+        &self,
+        registration_policy: RegistrationPolicy,
+    ) -> Vec<ValidationError> {
+        self.duplicate_registrations
+            .iter()
+            .filter(|(_, policy)| *policy == registration_policy)
+            .map(|(duplicate, _)| duplicate.clone())
+            .collect()
+    }
+
+    /// Get the dependency graph of the registrations
+    pub fn graph(&self) -> ServiceGraph {
+        ServiceGraph::new(self)
+    }
+
+    /// Validate the service container and get a root service provider
+    pub fn build_validated(&self) -> Result<RootServiceProvider, Error> {
+        self.validate()?;
+
+        Ok(self.build())
+    }
+}
+
+/// Get all the service definitions of the collection with their key in registration order.
+pub(crate) fn sort_service_definitions(
+    service_collection: &ServiceCollection,
+) -> Vec<(&ServiceKey, &ServiceDefinition)> {
+    let mut service_definitions: Vec<_> = service_collection
+        .iter()
+        .flat_map(|(key, service_definitions)| {
+            service_definitions
+                .iter()
+                .map(move |service_definition| (key, service_definition))
+        })
+        .collect();
+    service_definitions.sort_by_key(|(_, service_definition)| service_definition.id);
+
+    service_definitions
+}
+
+/// Wrap a service factory into a service init function.
+fn factory_init<T, F>(factory: F) -> ServiceInit
+where
+    T: Service + 'static,
+    F: Fn(&dyn ServiceProvider) -> T + Send + Sync + 'static,
+{
+    Arc::new(move |service_provider| Ok(Arc::new(factory(service_provider))))
+}
+
+/// Wrap a fallible service factory into a service init function.
+fn try_factory_init<T, F, E>(factory: F) -> ServiceInit
+where
+    T: Service + 'static,
+    F: Fn(&dyn ServiceProvider) -> Result<T, E> + Send + Sync + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    Arc::new(move |service_provider| {
+        factory(service_provider)
+            .map(|instance| Arc::new(instance) as _)
+            .map_err(|error| Error::FactoryFailed {
+                type_name: type_name::<T>().to_string(),
+                source: Arc::new(error),
+            })
+    })
 }
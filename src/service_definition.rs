@@ -1,12 +1,58 @@
-use std::sync::Arc;
+use std::{any::Any, future::Future, pin::Pin, sync::Arc};
 
 use crate::{
+    condition::Condition,
+    error::Error,
     service::{Service, ServiceProvider},
+    service_dependency::ServiceDependency,
+    service_key::ServiceKey,
     service_lifetime::ServiceLifetime,
 };
 
+/// Function creating a new instance of a service.
+pub type ServiceInit =
+    Arc<dyn Fn(&dyn ServiceProvider) -> Result<Arc<dyn Service>, Error> + Send + Sync>;
+
+/// Function creating a new instance of a service asynchronously from an owned service provider.
+pub type AsyncServiceInit = Arc<
+    dyn Fn(
+            Arc<dyn ServiceProvider + Send + Sync>,
+        ) -> Pin<Box<dyn Future<Output = Result<Arc<dyn Service>, Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Function checking an unmanaged instance when the service container is validated.
+pub type ServiceValidator = fn(&(dyn Any + Send + Sync)) -> Result<(), String>;
+
+/// Identifiers of the service container and of the registration.
+pub type RegistrationId = (usize, usize);
+
 #[derive(Clone)]
 pub struct ServiceDefinition {
+    /// Identifier of the registration in the service container.
+    pub id: usize,
+    /// Identifier of the service container declaring the registration.
+    pub container_id: usize,
+    /// Key of the implementation type.
+    pub implementation: ServiceKey,
     pub lifetime: ServiceLifetime,
-    pub init: std::sync::Arc<dyn Fn(&dyn ServiceProvider) -> Arc<dyn Service> + Send + Sync>,
+    pub init: ServiceInit,
+    /// Asynchronous init function, replacing `init` for the asynchronous services.
+    pub async_init: Option<AsyncServiceInit>,
+    /// Service resolver of the trait registrations.
+    pub resolver: Option<Arc<dyn Any + Send + Sync + 'static>>,
+    /// Services resolved by the init function.
+    pub dependencies: Vec<ServiceDependency>,
+    /// Condition enabling the registration, evaluated when the service provider is built.
+    pub condition: Option<Condition>,
+    /// Check of the unmanaged instance, run when the service container is validated.
+    pub validator: Option<ServiceValidator>,
+}
+
+impl ServiceDefinition {
+    /// Get the identifier of the registration across the service containers.
+    pub fn registration_id(&self) -> RegistrationId {
+        (self.container_id, self.id)
+    }
 }
@@ -0,0 +1,99 @@
+use crate::{
+    generic_service::{GenericInstantiation, GenericService, GenericTraitService},
+    service_key::ServiceKey,
+};
+
+/// How a dependency is resolved by a service.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// The last registration of the key is required.
+    Required,
+    /// All the registrations of the key are resolved, there can be none.
+    Collection,
+    /// The last registration of the key is required and resolved on first use.
+    Lazy,
+    /// The last registration of the key is required and created on demand.
+    Factory,
+    /// The last registration of the key is resolved if it exists.
+    Optional,
+}
+
+/// Dependency of a service on another registration, used to validate the service container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceDependency {
+    pub key: ServiceKey,
+    pub kind: DependencyKind,
+    /// Closed instantiation of an open generic registration, added when the key is not registered.
+    pub instantiation: Option<GenericInstantiation>,
+}
+
+impl ServiceDependency {
+    fn new(key: ServiceKey, kind: DependencyKind) -> Self {
+        Self {
+            key,
+            kind,
+            instantiation: None,
+        }
+    }
+
+    /// Dependency on the specified type or trait.
+    pub fn required<T: ?Sized + 'static>() -> Self {
+        Self::new(ServiceKey::of::<T>(), DependencyKind::Required)
+    }
+
+    /// Dependency on the specified type or trait registered under the key.
+    pub fn required_keyed<T: ?Sized + 'static>(key: &str) -> Self {
+        Self::new(ServiceKey::named::<T>(key), DependencyKind::Required)
+    }
+
+    /// Optional dependency on the specified type or trait.
+    pub fn optional<T: ?Sized + 'static>() -> Self {
+        Self::new(ServiceKey::of::<T>(), DependencyKind::Optional)
+    }
+
+    /// Optional dependency on the specified type or trait registered under the key.
+    pub fn optional_keyed<T: ?Sized + 'static>(key: &str) -> Self {
+        Self::new(ServiceKey::named::<T>(key), DependencyKind::Optional)
+    }
+
+    /// Lazy dependency on the specified type or trait.
+    pub fn lazy<T: ?Sized + 'static>() -> Self {
+        Self::new(ServiceKey::of::<T>(), DependencyKind::Lazy)
+    }
+
+    /// Lazy dependency on the specified type or trait registered under the key.
+    pub fn lazy_keyed<T: ?Sized + 'static>(key: &str) -> Self {
+        Self::new(ServiceKey::named::<T>(key), DependencyKind::Lazy)
+    }
+
+    /// Factory dependency on the specified type or trait.
+    pub fn factory<T: ?Sized + 'static>() -> Self {
+        Self::new(ServiceKey::of::<T>(), DependencyKind::Factory)
+    }
+
+    /// Factory dependency on the specified type or trait registered under the key.
+    pub fn factory_keyed<T: ?Sized + 'static>(key: &str) -> Self {
+        Self::new(ServiceKey::named::<T>(key), DependencyKind::Factory)
+    }
+
+    /// Dependency on all the implementations of the specified trait.
+    pub fn collection<T: ?Sized + 'static>() -> Self {
+        Self::new(ServiceKey::of::<T>(), DependencyKind::Collection)
+    }
+
+    /// Dependency on the specified instantiation of an open generic type.
+    pub fn generic<T: GenericService>() -> Self {
+        Self::required::<T>().with_instantiation(Some(GenericInstantiation::of::<T>()))
+    }
+
+    /// Dependency on the specified instantiation of an open generic trait.
+    pub fn generic_trait<T: ?Sized + GenericTraitService>() -> Self {
+        Self::required::<T>().with_instantiation(Some(GenericInstantiation::of_trait::<T>()))
+    }
+
+    /// Set the closed instantiation of the dependency.
+    pub fn with_instantiation(mut self, instantiation: Option<GenericInstantiation>) -> Self {
+        self.instantiation = instantiation;
+        self
+    }
+}
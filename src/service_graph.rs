@@ -0,0 +1,294 @@
+use std::fmt::Write;
+
+use crate::{
+    service_container::{ServiceContainer, sort_service_definitions},
+    service_dependency::DependencyKind,
+    service_key::ServiceKey,
+    service_lifetime::ServiceLifetime,
+};
+
+/// Dependency graph of the registrations of a service container.
+#[derive(Clone, Debug)]
+pub struct ServiceGraph {
+    /// Registrations in declaration order.
+    pub services: Vec<ServiceNode>,
+}
+
+/// Registration of the service graph.
+#[derive(Clone, Debug)]
+pub struct ServiceNode {
+    /// Identifier of the registration in the service container.
+    pub id: usize,
+    /// Key of the registration, the trait for the trait registrations.
+    pub key: ServiceKey,
+    pub implementation: ServiceKey,
+    pub lifetime: ServiceLifetime,
+    pub dependencies: Vec<ServiceEdge>,
+}
+
+/// Dependency of a registration of the service graph.
+#[derive(Clone, Debug)]
+pub struct ServiceEdge {
+    pub key: ServiceKey,
+    pub kind: DependencyKind,
+    /// Identifiers of the registrations resolved for the dependency, empty if it is missing.
+    pub resolved: Vec<usize>,
+}
+
+impl ServiceGraph {
+    /// Create the dependency graph of the service container.
+    pub fn new(service_container: &ServiceContainer) -> Self {
+        let service_collection = service_container.get_enabled_service_collection();
+        let services = sort_service_definitions(&service_collection)
+            .into_iter()
+            .map(|(key, service_definition)| ServiceNode {
+                id: service_definition.id,
+                key: key.clone(),
+                implementation: service_definition.implementation.clone(),
+                lifetime: service_definition.lifetime,
+                dependencies: service_definition
+                    .dependencies
+                    .iter()
+                    .map(|dependency| {
+                        let service_definitions = service_collection
+                            .get(&dependency.key)
+                            .map(Vec::as_slice)
+                            .unwrap_or_default();
+                        let resolved = match dependency.kind {
+                            DependencyKind::Required
+                            | DependencyKind::Optional
+                            | DependencyKind::Lazy
+                            | DependencyKind::Factory => service_definitions
+                                .last()
+                                .map(|service_definition| service_definition.id)
+                                .into_iter()
+                                .collect(),
+                            DependencyKind::Collection => service_definitions
+                                .iter()
+                                .map(|service_definition| service_definition.id)
+                                .collect(),
+                        };
+
+                        ServiceEdge {
+                            key: dependency.key.clone(),
+                            kind: dependency.kind,
+                            resolved,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self { services }
+    }
+
+    /// Export the graph in the Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph services {\n    rankdir=LR;\n");
+
+        for service in &self.services {
+            writeln!(
+                dot,
+                "    s{} [label=\"{}\"];",
+                service.id,
+                node_label(service, "\\n", escape_dot)
+            )
+            .unwrap();
+        }
+
+        for service in &self.services {
+            for (index, dependency) in service.dependencies.iter().enumerate() {
+                let style = match dependency.kind {
+                    DependencyKind::Required => "",
+                    DependencyKind::Collection | DependencyKind::Optional => " [style=dashed]",
+                    DependencyKind::Lazy => " [style=dotted]",
+                    DependencyKind::Factory => " [style=dotted, arrowhead=empty]",
+                };
+
+                if dependency.resolved.is_empty() {
+                    writeln!(
+                        dot,
+                        "    m{}_{} [label=\"{}\", color={}];\n    s{} -> m{}_{}{};",
+                        service.id,
+                        index,
+                        escape_dot(&dependency.key.to_string()),
+                        missing_color(dependency.kind),
+                        service.id,
+                        service.id,
+                        index,
+                        style
+                    )
+                    .unwrap();
+                }
+
+                for resolved in &dependency.resolved {
+                    writeln!(dot, "    s{} -> s{}{};", service.id, resolved, style).unwrap();
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export the graph as a Mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+
+        for service in &self.services {
+            writeln!(
+                mermaid,
+                "    s{}[\"{}\"]",
+                service.id,
+                node_label(service, "<br/>", escape_mermaid)
+            )
+            .unwrap();
+        }
+
+        for service in &self.services {
+            for (index, dependency) in service.dependencies.iter().enumerate() {
+                let arrow = match dependency.kind {
+                    DependencyKind::Required => "-->",
+                    DependencyKind::Collection | DependencyKind::Optional => "-.->",
+                    DependencyKind::Lazy => "-. lazy .->",
+                    DependencyKind::Factory => "-. factory .->",
+                };
+
+                if dependency.resolved.is_empty() {
+                    writeln!(
+                        mermaid,
+                        "    s{} {} m{}_{}[\"{}\"]\n    style m{}_{} stroke:{}",
+                        service.id,
+                        arrow,
+                        service.id,
+                        index,
+                        escape_mermaid(&dependency.key.to_string()),
+                        service.id,
+                        index,
+                        missing_color(dependency.kind)
+                    )
+                    .unwrap();
+                }
+
+                for resolved in &dependency.resolved {
+                    writeln!(mermaid, "    s{} {} s{}", service.id, arrow, resolved).unwrap();
+                }
+            }
+        }
+
+        mermaid
+    }
+
+    /// Export the graph as a JSON document.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"services\": [");
+
+        for (service_index, service) in self.services.iter().enumerate() {
+            if service_index > 0 {
+                json.push(',');
+            }
+
+            write!(
+                json,
+                "\n    {{\n      \"id\": {},\n      \"key\": {},\n      \"name\": {},\n      \"implementation\": {},\n      \"lifetime\": \"{:?}\",\n      \"dependencies\": [",
+                service.id,
+                json_string(service.key.type_name()),
+                json_option(service.key.name()),
+                json_string(service.implementation.type_name()),
+                service.lifetime
+            )
+            .unwrap();
+
+            for (dependency_index, dependency) in service.dependencies.iter().enumerate() {
+                if dependency_index > 0 {
+                    json.push(',');
+                }
+
+                let resolved: Vec<String> = dependency
+                    .resolved
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+
+                write!(
+                    json,
+                    "\n        {{ \"key\": {}, \"name\": {}, \"kind\": \"{:?}\", \"resolved\": [{}] }}",
+                    json_string(dependency.key.type_name()),
+                    json_option(dependency.key.name()),
+                    dependency.kind,
+                    resolved.join(", ")
+                )
+                .unwrap();
+            }
+
+            if !service.dependencies.is_empty() {
+                json.push_str("\n      ");
+            }
+
+            json.push_str("]\n    }");
+        }
+
+        if !self.services.is_empty() {
+            json.push_str("\n  ");
+        }
+
+        json.push_str("]\n}\n");
+        json
+    }
+}
+
+/// Label of a registration: its key, its implementation for trait registrations and its lifetime.
+fn node_label(service: &ServiceNode, separator: &str, escape: fn(&str) -> String) -> String {
+    let mut parts = vec![escape(&service.key.to_string())];
+
+    if service.key.type_id() != service.implementation.type_id() {
+        parts.push(escape(&service.implementation.to_string()));
+    }
+
+    parts.push(format!("{:?}", service.lifetime));
+    parts.join(separator)
+}
+
+/// Color of a dependency without registration, gray if it is not required.
+fn missing_color(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Collection | DependencyKind::Optional => "gray",
+        _ => "red",
+    }
+}
+
+/// Escape a DOT label.
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape a Mermaid label.
+fn escape_mermaid(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Encode a JSON string.
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+/// Encode an optional JSON string.
+fn json_option(value: Option<&str>) -> String {
+    value.map(json_string).unwrap_or_else(|| "null".to_string())
+}
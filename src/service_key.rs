@@ -0,0 +1,80 @@
+use std::{
+    any::TypeId,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// Key of a registration in the service container.
+///
+/// Registrations are identified by the `TypeId` of the service type and an optional name
+/// for keyed services, the type name is only kept to produce readable diagnostics.
+#[derive(Clone)]
+pub struct ServiceKey {
+    type_id: TypeId,
+    type_name: &'static str,
+    name: Option<String>,
+}
+
+impl ServiceKey {
+    /// Create the key of the specified type or trait.
+    pub fn of<T: ?Sized + 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+            name: None,
+        }
+    }
+
+    /// Create the key of the specified type or trait registered under a name.
+    pub fn named<T: ?Sized + 'static>(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::of::<T>()
+        }
+    }
+
+    /// Get the type identifier of the key.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Get the type name of the key (diagnostic only).
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Get the name of the key if the service is keyed.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl PartialEq for ServiceKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id && self.name == other.name
+    }
+}
+
+impl Eq for ServiceKey {}
+
+impl Hash for ServiceKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_id.hash(state);
+        self.name.hash(state);
+    }
+}
+
+impl fmt::Debug for ServiceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for ServiceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} (\"{}\")", self.type_name, name),
+            None => f.write_str(self.type_name),
+        }
+    }
+}
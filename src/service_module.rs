@@ -0,0 +1,34 @@
+use crate::service_container::ServiceContainer;
+
+/// Group of registrations installed together in a service container.
+pub trait ServiceModule: 'static {
+    /// Declare the services of the module in the service container.
+    fn register(service_container: &mut ServiceContainer);
+
+    /// Modules installed before this module.
+    fn dependencies() -> Vec<ModuleDependency> {
+        Vec::new()
+    }
+}
+
+/// Dependency of a module on another module.
+#[derive(Clone, Copy, Debug)]
+pub struct ModuleDependency {
+    pub(crate) type_name: &'static str,
+    pub(crate) install: fn(&mut ServiceContainer),
+}
+
+impl ModuleDependency {
+    /// Dependency on the specified module.
+    pub fn of<M: ServiceModule>() -> Self {
+        Self {
+            type_name: std::any::type_name::<M>(),
+            install: |service_container| service_container.add_module::<M>(),
+        }
+    }
+
+    /// Get the type name of the module.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
@@ -0,0 +1,246 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    registration_policy::RegistrationPolicy,
+    service_container::{ServiceCollection, ServiceContainer, sort_service_definitions},
+    service_definition::ServiceDefinition,
+    service_dependency::{DependencyKind, ServiceDependency},
+    service_key::ServiceKey,
+    service_lifetime::ServiceLifetime,
+};
+
+/// Problem found in the registrations of a service container.
+#[derive(Clone, Debug)]
+pub enum ValidationError {
+    /// A required dependency is not registered.
+    MissingDependency {
+        service: ServiceKey,
+        dependency: ServiceKey,
+    },
+    /// Services depending on each other, the first and last keys of the path are the same.
+    CircularDependency { path: Vec<ServiceKey> },
+    /// Modules depending on each other, the first and last modules of the path are the same.
+    CircularModuleDependency { path: Vec<String> },
+    /// A singleton depends on a scoped or transient service which would be captured.
+    CaptiveDependency {
+        service: ServiceKey,
+        dependency: ServiceKey,
+        dependency_lifetime: ServiceLifetime,
+    },
+    /// The implementation is declared twice for the service with the `Error` registration policy.
+    DuplicateRegistration {
+        service: ServiceKey,
+        implementation: ServiceKey,
+    },
+    /// The validator of the options rejected their value.
+    InvalidOptions {
+        options: ServiceKey,
+        message: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MissingDependency {
+                service,
+                dependency,
+            } => write!(
+                f,
+                "{} depends on {} which is not registered",
+                service, dependency
+            ),
+            ValidationError::CircularDependency { path } => {
+                let path: Vec<String> = path.iter().map(ToString::to_string).collect();
+                write!(f, "circular dependency {}", path.join(" -> "))
+            }
+            ValidationError::CircularModuleDependency { path } => {
+                write!(f, "circular module dependency {}", path.join(" -> "))
+            }
+            ValidationError::CaptiveDependency {
+                service,
+                dependency,
+                dependency_lifetime,
+            } => write!(
+                f,
+                "singleton {} depends on {:?} {}",
+                service, dependency_lifetime, dependency
+            ),
+            ValidationError::DuplicateRegistration {
+                service,
+                implementation,
+            } => write!(
+                f,
+                "{} is already registered for {}",
+                implementation, service
+            ),
+            ValidationError::InvalidOptions { options, message } => {
+                write!(f, "invalid options {}: {}", options, message)
+            }
+        }
+    }
+}
+
+/// Visit state of a registration while searching the circular dependencies.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum VisitState {
+    Visiting,
+    Visited,
+}
+
+/// Check the dependencies of all the registrations of the service container.
+pub(crate) fn validate_service_container(
+    service_container: &ServiceContainer,
+) -> Vec<ValidationError> {
+    let mut validation_errors =
+        service_container.duplicate_registrations(RegistrationPolicy::Error);
+    validation_errors.extend_from_slice(service_container.module_cycles());
+    let service_collection = service_container.get_enabled_service_collection();
+    let service_definitions = sort_service_definitions(&service_collection);
+
+    for (key, service_definition) in &service_definitions {
+        // Check the value of the options
+        if let (Some(validator), Some(instance)) = (
+            service_definition.validator,
+            service_container
+                .unmanaged_services
+                .get(&service_definition.id),
+        ) && let Err(message) = validator(&*instance.clone().as_any())
+        {
+            validation_errors.push(ValidationError::InvalidOptions {
+                options: (*key).clone(),
+                message,
+            });
+        }
+
+        // Unmanaged instances are not created by the service provider
+        if service_definition.lifetime == ServiceLifetime::Unmanaged {
+            continue;
+        }
+
+        for dependency in &service_definition.dependencies {
+            let resolved = resolve_dependency(&service_collection, dependency);
+
+            if resolved.is_empty()
+                && !matches!(
+                    dependency.kind,
+                    DependencyKind::Collection | DependencyKind::Optional
+                )
+            {
+                validation_errors.push(ValidationError::MissingDependency {
+                    service: service_definition.implementation.clone(),
+                    dependency: dependency.key.clone(),
+                });
+            }
+
+            // A factory creates new instances on demand, they are not captured by the singleton
+            if service_definition.lifetime == ServiceLifetime::Singleton
+                && dependency.kind != DependencyKind::Factory
+            {
+                for dependency_definition in resolved {
+                    if matches!(
+                        dependency_definition.lifetime,
+                        ServiceLifetime::Scoped | ServiceLifetime::Transient
+                    ) {
+                        validation_errors.push(ValidationError::CaptiveDependency {
+                            service: service_definition.implementation.clone(),
+                            dependency: dependency.key.clone(),
+                            dependency_lifetime: dependency_definition.lifetime,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Search the circular dependencies from every registration
+    let mut visit_states = HashMap::new();
+    let mut path = Vec::new();
+
+    for (key, service_definition) in &service_definitions {
+        if !visit_states.contains_key(&service_definition.id) {
+            visit_dependencies(
+                &service_collection,
+                key,
+                service_definition,
+                &mut visit_states,
+                &mut path,
+                &mut validation_errors,
+            );
+        }
+    }
+
+    validation_errors
+}
+
+/// Get the registrations resolved for a dependency.
+fn resolve_dependency<'a>(
+    service_collection: &'a ServiceCollection,
+    dependency: &ServiceDependency,
+) -> &'a [ServiceDefinition] {
+    let service_definitions = service_collection
+        .get(&dependency.key)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    match dependency.kind {
+        DependencyKind::Required
+        | DependencyKind::Optional
+        | DependencyKind::Lazy
+        | DependencyKind::Factory => {
+            &service_definitions[service_definitions.len().saturating_sub(1)..]
+        }
+        DependencyKind::Collection => service_definitions,
+    }
+}
+
+/// Depth first search of the circular dependencies.
+fn visit_dependencies<'a>(
+    service_collection: &'a ServiceCollection,
+    key: &'a ServiceKey,
+    service_definition: &'a ServiceDefinition,
+    visit_states: &mut HashMap<usize, VisitState>,
+    path: &mut Vec<(usize, &'a ServiceKey)>,
+    validation_errors: &mut Vec<ValidationError>,
+) {
+    visit_states.insert(service_definition.id, VisitState::Visiting);
+    path.push((service_definition.id, key));
+
+    // Lazy and factory dependencies are resolved after the creation of the service
+    for dependency in service_definition.dependencies.iter().filter(|dependency| {
+        !matches!(
+            dependency.kind,
+            DependencyKind::Lazy | DependencyKind::Factory
+        )
+    }) {
+        for dependency_definition in resolve_dependency(service_collection, dependency) {
+            match visit_states.get(&dependency_definition.id) {
+                Some(VisitState::Visiting) => {
+                    let start = path
+                        .iter()
+                        .position(|(id, _)| *id == dependency_definition.id)
+                        .unwrap_or_default();
+                    let mut cycle: Vec<ServiceKey> = path[start..]
+                        .iter()
+                        .map(|(_, key)| (*key).clone())
+                        .collect();
+                    cycle.push(dependency.key.clone());
+
+                    validation_errors.push(ValidationError::CircularDependency { path: cycle });
+                }
+                Some(VisitState::Visited) => {}
+                None => visit_dependencies(
+                    service_collection,
+                    &dependency.key,
+                    dependency_definition,
+                    visit_states,
+                    path,
+                    validation_errors,
+                ),
+            }
+        }
+    }
+
+    path.pop();
+    visit_states.insert(service_definition.id, VisitState::Visited);
+}
@@ -0,0 +1,331 @@
+#[cfg(feature = "async")]
+mod async_tests {
+    use shive::async_service::{
+        AsyncService, get_instance_async, get_keyed_instance_async, get_trait_instance_async,
+    };
+    use shive::decorator::Decorator;
+    use shive::service::{Service, ServiceProvider, ServiceResolver, get_instance};
+    use shive::service_container::ServiceContainer;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    pub trait Database: Send + Sync {
+        fn is_connected(&self) -> bool;
+    }
+
+    pub struct Counter {
+        count: AtomicUsize,
+    }
+
+    impl Counter {
+        pub fn new() -> Self {
+            Self {
+                count: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn count(&self) -> usize {
+            self.count.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Service for Counter {
+        fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+        where
+            Self: Sized,
+        {
+            Arc::new(Self::new())
+        }
+
+        fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+            self
+        }
+    }
+
+    pub struct Connection {
+        id: usize,
+    }
+
+    impl Service for Connection {
+        fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+        where
+            Self: Sized,
+        {
+            unreachable!("Connection is created asynchronously")
+        }
+
+        fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+            self
+        }
+    }
+
+    impl AsyncService for Connection {
+        async fn init(
+            service_provider: Arc<dyn ServiceProvider + Send + Sync>,
+        ) -> Arc<dyn Service> {
+            let counter = get_instance::<Counter>(service_provider.as_service_provider())
+                .expect("Cannot get counter");
+
+            // Connect to the database
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            Arc::new(Self {
+                id: counter.count.fetch_add(1, Ordering::SeqCst),
+            })
+        }
+    }
+
+    impl Database for Connection {
+        fn is_connected(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn get_instance_async_singleton_once_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_unmanaged::<Counter>(Counter::new());
+        service_container.add_async_singleton::<Connection>();
+        let service_provider = service_container.build();
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| tokio::spawn(get_instance_async::<Connection>(&service_provider)))
+            .collect();
+        let mut services = Vec::new();
+        for task in tasks {
+            services.push(task.await.unwrap().expect("Cannot get service"));
+        }
+
+        let counter = get_instance::<Counter>(&service_provider).expect("Cannot get counter");
+        assert_eq!(counter.count(), 1);
+        assert!(
+            services
+                .iter()
+                .all(|service| Arc::ptr_eq(service, &services[0]))
+        );
+
+        // The initialized singleton can be resolved synchronously
+        let service = get_instance::<Connection>(&service_provider).expect("Cannot get service");
+        assert!(Arc::ptr_eq(&service, &services[0]));
+    }
+
+    #[tokio::test]
+    async fn get_instance_async_scoped_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_unmanaged::<Counter>(Counter::new());
+        service_container.add_async_scoped::<Connection>();
+        let service_provider = service_container.build();
+        let first_scope = service_provider.create_scope();
+        let second_scope = service_provider.create_scope();
+
+        // Asynchronous services cannot be created synchronously
+        assert!(get_instance::<Connection>(&first_scope).is_err());
+
+        let first = get_instance_async::<Connection>(&first_scope)
+            .await
+            .expect("Cannot get service");
+        let first_again = get_instance_async::<Connection>(&first_scope)
+            .await
+            .expect("Cannot get service");
+        let second = get_instance_async::<Connection>(&second_scope)
+            .await
+            .expect("Cannot get service");
+
+        assert!(Arc::ptr_eq(&first, &first_again));
+        assert!(first.id != second.id);
+        assert!(
+            get_instance_async::<Connection>(&service_provider)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_trait_instance_async_transient_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_unmanaged::<Counter>(Counter::new());
+        service_container.add_async_trait_transient::<dyn Database, Connection>(ServiceResolver {
+            as_interface: |service| service.downcast::<Connection>().unwrap(),
+        });
+        let service_provider = service_container.build();
+        let scope = service_provider.create_scope();
+
+        let database = get_trait_instance_async::<dyn Database>(&scope)
+            .await
+            .expect("Cannot get service");
+        get_trait_instance_async::<dyn Database>(&scope)
+            .await
+            .expect("Cannot get service");
+
+        let counter = get_instance::<Counter>(&scope).expect("Cannot get counter");
+        assert!(database.is_connected());
+        assert_eq!(counter.count(), 2);
+        assert!(
+            get_keyed_instance_async::<Connection>(&scope, "primary")
+                .await
+                .is_err()
+        );
+    }
+
+    pub struct ReadOnlyDatabase {
+        inner: Arc<dyn Database>,
+    }
+
+    impl Database for ReadOnlyDatabase {
+        fn is_connected(&self) -> bool {
+            self.inner.is_connected()
+        }
+    }
+
+    impl Decorator<dyn Database> for ReadOnlyDatabase {
+        fn decorate(inner: Arc<dyn Database>, _: &dyn ServiceProvider) -> Arc<dyn Database> {
+            Arc::new(ReadOnlyDatabase { inner })
+        }
+    }
+
+    #[tokio::test]
+    async fn get_decorated_trait_instance_async_singleton_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_unmanaged::<Counter>(Counter::new());
+        service_container.add_async_trait_singleton::<dyn Database, Connection>(ServiceResolver {
+            as_interface: |service| service.downcast::<Connection>().unwrap(),
+        });
+        service_container.decorate::<dyn Database, ReadOnlyDatabase>();
+        let service_provider = service_container.build();
+
+        let first = get_trait_instance_async::<dyn Database>(&service_provider)
+            .await
+            .expect("Cannot get service");
+        let second = get_trait_instance_async::<dyn Database>(&service_provider)
+            .await
+            .expect("Cannot get service");
+
+        let counter = get_instance::<Counter>(&service_provider).expect("Cannot get counter");
+        assert!(first.is_connected());
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(counter.count(), 1);
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive_async_tests {
+        use super::{Connection, Counter, Database};
+        use shive::async_service::get_instance_async;
+        use shive::error::Error;
+        use shive::lazy::Lazy;
+        use shive::service::{Service, ServiceResolver, get_instance};
+        use shive::service_container::ServiceContainer;
+        use shive_derive::AsyncService;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        #[derive(AsyncService)]
+        pub struct Repository {
+            connection: Arc<Connection>,
+            database: Arc<dyn Database>,
+            counter: Lazy<Counter>,
+        }
+
+        #[derive(AsyncService)]
+        pub struct CycleFirst {
+            _second: Arc<CycleSecond>,
+        }
+
+        #[derive(AsyncService)]
+        pub struct CycleSecond {
+            _first: Arc<CycleFirst>,
+        }
+
+        #[tokio::test]
+        async fn get_derive_instance_async_cycle_ko() {
+            let mut service_container = ServiceContainer::new();
+            service_container.add_unmanaged::<Counter>(Counter::new());
+            service_container.add_async_singleton::<CycleFirst>();
+            service_container.add_async_singleton::<CycleSecond>();
+            let service_provider = service_container.build();
+            let scope = service_provider.create_scope();
+
+            let result = tokio::time::timeout(
+                Duration::from_secs(1),
+                get_instance_async::<CycleFirst>(&scope),
+            )
+            .await
+            .expect("Circular resolution is not detected");
+
+            match result {
+                Err(Error::Cycle { path }) => {
+                    assert_eq!(path.len(), 3);
+                    assert!(path[0].ends_with("CycleFirst"));
+                    assert!(path[1].ends_with("CycleSecond"));
+                    assert!(path[2].ends_with("CycleFirst"));
+                }
+                _ => panic!("Circular resolution is not reported"),
+            }
+
+            // Scoped instances depending on each other
+            let mut service_container = ServiceContainer::new();
+            service_container.add_unmanaged::<Counter>(Counter::new());
+            service_container.add_async_scoped::<CycleFirst>();
+            service_container.add_async_scoped::<CycleSecond>();
+            let service_provider = service_container.build();
+            let scope = service_provider.create_scope();
+
+            let result = tokio::time::timeout(
+                Duration::from_secs(1),
+                get_instance_async::<CycleSecond>(&scope),
+            )
+            .await
+            .expect("Circular resolution is not detected");
+
+            assert!(matches!(result, Err(Error::Cycle { .. })));
+        }
+
+        #[tokio::test]
+        async fn get_derive_instance_async_ok() {
+            let mut service_container = ServiceContainer::new();
+            service_container.add_unmanaged::<Counter>(Counter::new());
+            service_container.add_async_singleton::<Connection>();
+            service_container.add_async_trait_singleton::<dyn Database, Connection>(
+                ServiceResolver {
+                    as_interface: |service| service.downcast::<Connection>().unwrap(),
+                },
+            );
+            service_container.add_async_scoped::<Repository>();
+            let service_provider = service_container.build();
+            let scope = service_provider.create_scope();
+
+            let repository = get_instance_async::<Repository>(&scope)
+                .await
+                .expect("Cannot get service");
+
+            assert!(repository.database.is_connected());
+            assert!(repository.connection.id < 2);
+            assert_eq!(
+                repository
+                    .counter
+                    .get()
+                    .expect("Cannot get counter")
+                    .count(),
+                2
+            );
+            assert!(service_container.validate().is_ok());
+        }
+
+        #[test]
+        fn get_derive_instance_sync_ko() {
+            let mut service_container = ServiceContainer::new();
+            service_container.add_unmanaged::<Counter>(Counter::new());
+            service_container.add_singleton::<CycleFirst>();
+            let service_provider = service_container.build();
+
+            let result = get_instance::<CycleFirst>(&service_provider);
+
+            match result {
+                Err(Error::AsyncInitRequired { type_name }) => {
+                    assert!(type_name.ends_with("CycleFirst"))
+                }
+                _ => panic!("Synchronous resolution is not reported"),
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(feature = "config")]
+mod config_tests {
+    use serde::Deserialize;
+    use shive::{
+        config::{from_env, from_file, from_json, from_toml},
+        error::Error,
+        options::{Options, Validate},
+        service::get_instance,
+        service_container::ServiceContainer,
+    };
+
+    #[derive(Deserialize)]
+    pub struct DatabaseConfig {
+        pub url: String,
+        pub pool_size: u32,
+        #[serde(default)]
+        pub read_only: bool,
+    }
+
+    impl Validate for DatabaseConfig {}
+
+    #[test]
+    fn from_json_ok() {
+        let json = r#"{ "app": { "database": { "url": "postgres://json", "pool_size": 5 } } }"#;
+        let config = from_json::<DatabaseConfig>(json, "app.database").expect("Cannot bind");
+
+        assert_eq!(config.url, "postgres://json");
+        assert_eq!(config.pool_size, 5);
+        assert!(!config.read_only);
+    }
+
+    #[test]
+    fn from_toml_ok() {
+        let toml = "[database]\nurl = \"postgres://toml\"\npool_size = 3\nread_only = true\n";
+        let mut service_container = ServiceContainer::new();
+        service_container
+            .add_options(from_toml::<DatabaseConfig>(toml, "database").expect("Cannot bind"));
+        let service_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+
+        let options =
+            get_instance::<Options<DatabaseConfig>>(&service_provider).expect("Cannot get");
+        assert_eq!(options.url, "postgres://toml");
+        assert_eq!(options.pool_size, 3);
+        assert!(options.read_only);
+    }
+
+    #[test]
+    fn from_section_ko() {
+        let json = r#"{ "database": { "url": "postgres://json" } }"#;
+
+        assert!(matches!(
+            from_json::<DatabaseConfig>(json, "cache"),
+            Err(Error::Configuration(_))
+        ));
+        assert!(matches!(
+            from_json::<DatabaseConfig>(json, "database"),
+            Err(Error::Configuration(_))
+        ));
+        assert!(matches!(
+            from_file::<DatabaseConfig>("Cargo.lock.ini", "database"),
+            Err(Error::Configuration(_))
+        ));
+    }
+
+    #[derive(Deserialize)]
+    pub struct AppConfig {
+        pub name: String,
+        pub database: DatabaseConfig,
+    }
+
+    #[test]
+    fn from_env_ok() {
+        // SAFETY: the variables are only read by the tests of this prefix
+        unsafe {
+            std::env::set_var("SHIVE_ENV_OK_NAME", "shive");
+            std::env::set_var("SHIVE_ENV_OK_DATABASE__URL", "postgres://env");
+            std::env::set_var("SHIVE_ENV_OK_DATABASE__POOL_SIZE", "8");
+        }
+
+        let config = from_env::<AppConfig>("SHIVE_ENV_OK").expect("Cannot bind");
+        assert_eq!(config.name, "shive");
+        assert_eq!(config.database.url, "postgres://env");
+        assert_eq!(config.database.pool_size, 8);
+        assert!(!config.database.read_only);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn from_env_invalid_unicode_ko() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        // SAFETY: the variables are only read by the tests of this prefix
+        unsafe {
+            std::env::set_var(OsStr::from_bytes(b"SHIVE_ENV_KO_\xff"), "ignored");
+            std::env::set_var("SHIVE_ENV_KO_NAME", OsStr::from_bytes(b"\xff"));
+        }
+
+        let result = from_env::<AppConfig>("SHIVE_ENV_KO");
+        assert!(
+            matches!(result, Err(Error::Configuration(message)) if message.contains("SHIVE_ENV_KO_NAME"))
+        );
+    }
+}
@@ -1,11 +1,21 @@
+#![allow(clippy::bool_assert_comparison)]
+
 #[cfg(feature = "derive")]
 mod derive_service_tests {
-    use shive::service::Service;
-    use shive::service::ServiceProvider;
+    use shive::decorator::Decorator;
+    use shive::dispose::Dispose;
+    use shive::error::Error;
+    use shive::factory::Factory;
+    use shive::generic_service::{GenericService, GenericTraitService};
+    use shive::lazy::Lazy;
+    use shive::options::{Options, Validate};
+    use shive::service::{Service, ServiceProvider};
     use shive::service::{get_trait_instance, ServiceResolver};
+    use shive::validation::ValidationError;
     use shive::{service::get_instance, service_container::ServiceContainer};
     use shive_derive::Service;
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     pub trait TestTrait: Sync + Send {
         fn is_trait_ok(&self) -> bool;
@@ -41,6 +51,350 @@ mod derive_service_tests {
         }
     }
 
+    static DISPOSED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Service)]
+    #[dispose]
+    pub struct DisposableServiceTestDerive {
+        test_derive: Arc<ServiceTestType>,
+    }
+
+    impl Dispose for DisposableServiceTestDerive {
+        fn dispose(&self) {
+            assert!(self.test_derive.is_ok());
+            DISPOSED_COUNT.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    static LAZY_DISPOSED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Service)]
+    #[dispose]
+    pub struct LazyDisposableServiceTestDerive {
+        test_derive: Lazy<ServiceTestType>,
+        factory: Factory<ServiceTestType>,
+    }
+
+    impl Dispose for LazyDisposableServiceTestDerive {
+        fn dispose(&self) {
+            LAZY_DISPOSED_COUNT.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[derive(Service)]
+    pub struct KeyedCallerServiceTestDerive {
+        #[inject(key = "primary")]
+        primary: Arc<dyn TestTrait>,
+        #[inject(key = "replica")]
+        replica: Arc<ServiceTestType>,
+    }
+
+    pub struct RepositoryTestDerive<T: 'static> {
+        marker: std::marker::PhantomData<fn() -> T>,
+    }
+
+    impl<T: 'static> Service for RepositoryTestDerive<T> {
+        fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+        where
+            Self: Sized,
+        {
+            Arc::new(Self {
+                marker: std::marker::PhantomData,
+            })
+        }
+
+        fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+            self
+        }
+    }
+
+    pub struct RepositoryDefinition;
+
+    impl<T: 'static> GenericService for RepositoryTestDerive<T> {
+        type Definition = RepositoryDefinition;
+    }
+
+    pub trait RepoTestTrait<T>: Send + Sync + 'static {}
+
+    impl<T: 'static> RepoTestTrait<T> for RepositoryTestDerive<T> {}
+
+    pub struct RepoTraitDefinition;
+
+    impl<T: 'static> GenericTraitService for dyn RepoTestTrait<T> {
+        type Definition = RepoTraitDefinition;
+        type Implementation = RepositoryTestDerive<T>;
+
+        fn resolver() -> ServiceResolver<Self> {
+            ServiceResolver::<dyn RepoTestTrait<T>> {
+                as_interface: |resolver| resolver.downcast::<RepositoryTestDerive<T>>().unwrap(),
+            }
+        }
+    }
+
+    #[derive(Service)]
+    pub struct GenericCallerTestDerive {
+        repository: Arc<RepositoryTestDerive<ServiceTestType>>,
+        repo_trait: Lazy<dyn RepoTestTrait<ServiceTestType>>,
+    }
+
+    #[test]
+    fn get_derive_keyed_instance_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_scoped::<KeyedCallerServiceTestDerive>();
+        service_container.add_keyed_trait_singleton::<dyn TestTrait, ServiceTestType>(
+            "primary",
+            ServiceResolver::<dyn TestTrait> {
+                as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+            },
+        );
+        service_container.add_keyed_singleton::<ServiceTestType>("replica");
+
+        let service_provider = service_container.build();
+        let scope = service_provider.create_scope();
+        let service =
+            get_instance::<KeyedCallerServiceTestDerive>(&scope).expect("Cannot get service");
+
+        assert!(service.primary.is_trait_ok());
+        assert!(service.replica.is_ok());
+    }
+
+    pub struct CacheConfig {
+        pub capacity: usize,
+    }
+
+    impl Validate for CacheConfig {}
+
+    #[derive(Service)]
+    pub struct OptionsCallerServiceTestDerive {
+        config: Arc<Options<CacheConfig>>,
+        #[inject(key = "sessions")]
+        sessions: Arc<Options<CacheConfig>>,
+    }
+
+    #[test]
+    fn get_derive_options_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<OptionsCallerServiceTestDerive>();
+        service_container.add_options(CacheConfig { capacity: 100 });
+        service_container.add_named_options("sessions", CacheConfig { capacity: 10 });
+
+        let service_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+        let service = get_instance::<OptionsCallerServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+
+        assert_eq!(service.config.capacity, 100);
+        assert_eq!(service.sessions.capacity, 10);
+    }
+
+    #[derive(Service)]
+    pub struct LazyFirstServiceTestDerive {
+        second: Lazy<LazySecondServiceTestDerive>,
+        #[inject(key = "primary")]
+        handler: Lazy<dyn TestTrait>,
+    }
+
+    #[derive(Service)]
+    pub struct LazySecondServiceTestDerive {
+        first: Arc<LazyFirstServiceTestDerive>,
+    }
+
+    #[test]
+    fn get_derive_lazy_instance_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<LazyFirstServiceTestDerive>();
+        service_container.add_singleton::<LazySecondServiceTestDerive>();
+        service_container.add_keyed_trait_singleton::<dyn TestTrait, ServiceTestType>(
+            "primary",
+            ServiceResolver::<dyn TestTrait> {
+                as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+            },
+        );
+
+        // The lazy dependency breaks the cycle
+        let service_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+        let first = get_instance::<LazyFirstServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+        assert!(!first.second.is_created());
+        assert!(Arc::ptr_eq(&first.second.first, &first));
+        assert!(first.handler.is_trait_ok());
+    }
+
+    #[derive(Service)]
+    pub struct FactoryServiceTestDerive {
+        handlers: Factory<dyn TestTrait>,
+        #[inject(key = "replica")]
+        replicas: Factory<ServiceTestType>,
+    }
+
+    #[test]
+    fn get_derive_factory_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<FactoryServiceTestDerive>();
+        service_container.add_trait_singleton::<dyn TestTrait, ServiceTestType>(ServiceResolver::<
+            dyn TestTrait,
+        > {
+            as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+        });
+        service_container.add_keyed_singleton::<ServiceTestType>("replica");
+
+        let service_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+        let service = get_instance::<FactoryServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+
+        assert!(
+            service
+                .handlers
+                .create()
+                .expect("Cannot create")
+                .is_trait_ok()
+        );
+        let first = service.replicas.create().expect("Cannot create");
+        let second = service.replicas.create().expect("Cannot create");
+        assert!(!Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn validate_derive_factory_of_transient_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<FactoryServiceTestDerive>();
+        service_container.add_trait_transient::<dyn TestTrait, ServiceTestType>(ServiceResolver::<
+            dyn TestTrait,
+        > {
+            as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+        });
+        service_container.add_keyed_transient::<ServiceTestType>("replica");
+
+        let service_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+        let service = get_instance::<FactoryServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+
+        assert!(service.replicas.create().is_ok());
+    }
+
+    #[derive(Service)]
+    pub struct OptionalServiceTestDerive {
+        handler: Option<Arc<dyn TestTrait>>,
+        #[inject(key = "replica")]
+        replica: Option<Arc<ServiceTestType>>,
+    }
+
+    #[test]
+    fn get_derive_optional_instance_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<OptionalServiceTestDerive>();
+        service_container.add_keyed_singleton::<ServiceTestType>("replica");
+
+        let service_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+        let service = get_instance::<OptionalServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+
+        assert!(service.handler.is_none());
+        assert!(
+            service
+                .replica
+                .as_ref()
+                .is_some_and(|replica| replica.is_ok())
+        );
+    }
+
+    #[test]
+    fn validate_derive_dependencies() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<KeyedCallerServiceTestDerive>();
+        service_container.add_keyed_scoped::<ServiceTestType>("replica");
+        service_container.add_singleton::<CollectionCallerServiceTestDerive>();
+
+        let validation_errors = match service_container.validate() {
+            Err(Error::Validation(validation_errors)) => validation_errors,
+            _ => panic!("The container should not be valid"),
+        };
+
+        assert_eq!(validation_errors.len(), 2);
+        assert!(matches!(
+            &validation_errors[0],
+            ValidationError::MissingDependency { dependency, .. }
+                if dependency.name() == Some("primary")
+        ));
+        assert!(matches!(
+            &validation_errors[1],
+            ValidationError::CaptiveDependency { dependency, .. }
+                if dependency.name() == Some("replica")
+        ));
+    }
+
+    #[derive(Service)]
+    pub struct CollectionCallerServiceTestDerive {
+        handlers: Vec<Arc<dyn TestTrait>>,
+    }
+
+    #[test]
+    fn get_derive_all_trait_instances_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<CollectionCallerServiceTestDerive>();
+        let service_resolver = ServiceResolver::<dyn TestTrait> {
+            as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+        };
+        service_container.add_trait_singleton::<dyn TestTrait, ServiceTestType>(service_resolver);
+        let service_resolver = ServiceResolver::<dyn TestTrait> {
+            as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+        };
+        service_container.add_trait_unmanaged::<dyn TestTrait, ServiceTestType>(
+            service_resolver,
+            ServiceTestType {},
+        );
+
+        let service_provider = service_container.build();
+        let service = get_instance::<CollectionCallerServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+
+        assert_eq!(service.handlers.len(), 2);
+        assert!(service.handlers.iter().all(|handler| handler.is_trait_ok()));
+    }
+
+    pub struct NegateDecorator {
+        inner: Arc<dyn TestTrait>,
+    }
+
+    impl TestTrait for NegateDecorator {
+        fn is_trait_ok(&self) -> bool {
+            !self.inner.is_trait_ok()
+        }
+    }
+
+    impl Decorator<dyn TestTrait> for NegateDecorator {
+        fn decorate(inner: Arc<dyn TestTrait>, _: &dyn ServiceProvider) -> Arc<dyn TestTrait> {
+            Arc::new(NegateDecorator { inner })
+        }
+    }
+
+    #[test]
+    fn get_derive_decorated_trait_instance_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_scoped::<CallerServiceTestDerive>();
+        let service_resolver = ServiceResolver::<dyn TestTrait> {
+            as_interface: |resolver| resolver.downcast::<ServiceTestType>().unwrap(),
+        };
+        service_container.add_trait_scoped::<dyn TestTrait, ServiceTestType>(service_resolver);
+        service_container.decorate::<dyn TestTrait, NegateDecorator>();
+        service_container.add_singleton::<ServiceTestType>();
+
+        let service_provider = service_container.build();
+        let scope = service_provider.create_scope();
+        let service = get_instance::<CallerServiceTestDerive>(&scope).expect("Cannot get service");
+
+        assert!(!service.is_trait_ok());
+    }
+
     #[test]
     fn get_derive_instance_singleton_ok() {
         let mut service_container = ServiceContainer::new();
@@ -153,6 +507,23 @@ mod derive_service_tests {
         assert_eq!(service.is_ok(), true);
     }
 
+    #[test]
+    fn get_derive_instance_missing_dependency_ko() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<ServiceTestType>();
+        service_container.add_singleton::<CallerServiceTestDerive>();
+        let service_provider = service_container.build();
+
+        // The missing trait is returned as an error instead of panicking
+        let result = get_instance::<CallerServiceTestDerive>(&service_provider);
+
+        assert!(matches!(
+            result.as_ref().map_err(Error::root_cause),
+            Err(Error::NotRegistered { .. })
+        ));
+        assert!(matches!(result, Err(Error::Resolution { .. })));
+    }
+
     #[test]
     #[should_panic]
     fn get_derive_trait_instance_singleton_from_scoped_should_panic() {
@@ -230,4 +601,69 @@ mod derive_service_tests {
         let scope = service_provider.create_scope();
         let _ = get_instance::<CallerServiceTestDerive>(&scope).expect("Cannot get service");
     }
+
+    #[test]
+    fn dispose_derive_instance_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_scoped::<DisposableServiceTestDerive>();
+        service_container.add_singleton::<ServiceTestType>();
+        let service_provider = service_container.build();
+        let scope = service_provider.create_scope();
+        get_instance::<DisposableServiceTestDerive>(&scope).expect("Cannot get service");
+
+        scope.dispose();
+        assert_eq!(DISPOSED_COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn get_derive_instance_open_generic_dependencies_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_open_scoped::<RepositoryDefinition>();
+        service_container.add_open_scoped::<RepoTraitDefinition>();
+        service_container.add_scoped::<GenericCallerTestDerive>();
+        let service_provider = service_container.build();
+        let scope = service_provider.create_scope();
+        let caller = get_instance::<GenericCallerTestDerive>(&scope).expect("Cannot get service");
+        let repository = get_instance::<RepositoryTestDerive<ServiceTestType>>(&scope)
+            .expect("Cannot get service");
+
+        assert!(service_container.validate().is_ok());
+        assert!(Arc::ptr_eq(&caller.repository, &repository));
+        assert!(caller.repo_trait.get().is_ok());
+    }
+
+    #[test]
+    fn dispose_derive_instance_holding_provider_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_scoped::<LazyDisposableServiceTestDerive>();
+        service_container.add_singleton::<ServiceTestType>();
+        let service_provider = service_container.build();
+        let scope = service_provider.create_scope();
+        let service =
+            get_instance::<LazyDisposableServiceTestDerive>(&scope).expect("Cannot get service");
+        assert!(service.test_derive.is_ok());
+        assert!(service.factory.create().is_ok());
+        let weak_service = Arc::downgrade(&service);
+        drop(service);
+
+        // The lazy and the factory do not keep the scope alive
+        drop(scope);
+        assert_eq!(LAZY_DISPOSED_COUNT.load(Ordering::SeqCst), 1);
+        assert!(weak_service.upgrade().is_none());
+
+        // The singletons are released with the root provider
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<LazyDisposableServiceTestDerive>();
+        service_container.add_singleton::<ServiceTestType>();
+        let service_provider = service_container.build();
+        let service = get_instance::<LazyDisposableServiceTestDerive>(&service_provider)
+            .expect("Cannot get service");
+        assert!(service.test_derive.is_ok());
+        let weak_service = Arc::downgrade(&service);
+        drop(service);
+
+        drop(service_provider);
+        assert_eq!(LAZY_DISPOSED_COUNT.load(Ordering::SeqCst), 2);
+        assert!(weak_service.upgrade().is_none());
+    }
 }
@@ -0,0 +1,49 @@
+#[cfg(feature = "discovery")]
+mod discovery_tests {
+    use shive::service::{Service, get_instance, get_keyed_instance, get_trait_instance};
+    use shive::service_container::ServiceContainer;
+    use shive_derive::Service;
+    use std::sync::Arc;
+
+    pub trait Repository: Send + Sync {
+        fn is_connected(&self) -> bool;
+    }
+
+    #[shive::service]
+    #[derive(Service)]
+    pub struct Clock {}
+
+    #[shive::service(lifetime = "scoped", provides = "dyn Repository")]
+    #[derive(Service)]
+    pub struct SqlRepository {
+        clock: Arc<Clock>,
+    }
+
+    impl Repository for SqlRepository {
+        fn is_connected(&self) -> bool {
+            Arc::strong_count(&self.clock) > 1
+        }
+    }
+
+    #[shive::service(lifetime = "transient", key = "audit")]
+    #[derive(Service)]
+    pub struct AuditLog {}
+
+    #[test]
+    fn from_discovered_ok() {
+        let service_container = ServiceContainer::from_discovered();
+        assert_eq!(service_container.get_service_definitions().len(), 3);
+
+        let root_provider = service_container
+            .build_validated()
+            .expect("Invalid container");
+        let clock = get_instance::<Clock>(&root_provider).expect("Cannot get Clock");
+
+        let service_provider = root_provider.create_scope();
+        let repository =
+            get_trait_instance::<dyn Repository>(&service_provider).expect("Cannot get Repository");
+        assert!(repository.is_connected());
+        assert!(Arc::strong_count(&clock) > 1);
+        assert!(get_keyed_instance::<AuditLog>(&service_provider, "audit").is_ok());
+    }
+}
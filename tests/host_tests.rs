@@ -0,0 +1,153 @@
+#[cfg(feature = "host")]
+mod host_tests {
+    use shive::error::Error;
+    use shive::host::{Host, HostedFuture, HostedService};
+    use shive::service::{Service, ServiceProvider, get_instance};
+    use shive::service_container::ServiceContainer;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    pub struct Journal {
+        entries: Mutex<Vec<String>>,
+    }
+
+    impl Journal {
+        pub fn entries(&self) -> Vec<String> {
+            self.entries.lock().unwrap().clone()
+        }
+
+        fn write(&self, entry: &str) {
+            self.entries.lock().unwrap().push(entry.to_string());
+        }
+    }
+
+    impl Service for Journal {
+        fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+        where
+            Self: Sized,
+        {
+            Arc::new(Self {
+                entries: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+            self
+        }
+    }
+
+    /// Declare a hosted service writing its lifecycle in the journal.
+    macro_rules! journaled_service {
+        ($name:ident, $start:expr, $stop_delay:expr) => {
+            pub struct $name {
+                journal: Arc<Journal>,
+            }
+
+            impl Service for $name {
+                fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+                where
+                    Self: Sized,
+                {
+                    Arc::new(Self {
+                        journal: get_instance::<Journal>(service_provider)
+                            .expect("Cannot get journal"),
+                    })
+                }
+
+                fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+                    self
+                }
+            }
+
+            impl HostedService for $name {
+                fn start(&self) -> HostedFuture<'_> {
+                    Box::pin(async move {
+                        self.journal.write(concat!(stringify!($name), " started"));
+                        $start
+                    })
+                }
+
+                fn stop(&self) -> HostedFuture<'_> {
+                    Box::pin(async move {
+                        tokio::time::sleep($stop_delay).await;
+                        self.journal.write(concat!(stringify!($name), " stopped"));
+                        Ok(())
+                    })
+                }
+            }
+        };
+    }
+
+    journaled_service!(Worker, Ok(()), Duration::ZERO);
+    journaled_service!(Listener, Ok(()), Duration::ZERO);
+    journaled_service!(BrokenListener, Err("Cannot bind".into()), Duration::ZERO);
+    journaled_service!(SlowWorker, Ok(()), Duration::from_secs(60));
+
+    #[tokio::test]
+    async fn run_host_ok() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<Journal>();
+        service_container.add_hosted_service::<Worker>();
+        service_container.add_hosted_service::<Listener>();
+        let host = Host::new(&service_container);
+        let journal = get_instance::<Journal>(host.service_provider()).expect("Cannot get journal");
+        let shutdown_handle = host.shutdown_handle();
+
+        // The services are started when the shutdown is awaited
+        host.run_until(async {
+            assert_eq!(journal.entries(), ["Worker started", "Listener started"]);
+            shutdown_handle.shutdown();
+            std::future::pending().await
+        })
+        .await
+        .expect("Cannot run host");
+        assert_eq!(
+            journal.entries(),
+            [
+                "Worker started",
+                "Listener started",
+                "Listener stopped",
+                "Worker stopped"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn start_host_ko() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<Journal>();
+        service_container.add_hosted_service::<Worker>();
+        service_container.add_hosted_service::<BrokenListener>();
+        service_container.add_hosted_service::<Listener>();
+        let host = Host::new(&service_container);
+        let journal = get_instance::<Journal>(host.service_provider()).expect("Cannot get journal");
+
+        let result = host.run_until(std::future::pending()).await;
+
+        assert!(matches!(result, Err(Error::HostedServiceFailed { .. })));
+        assert_eq!(
+            journal.entries(),
+            ["Worker started", "BrokenListener started", "Worker stopped"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_host_timeout_ko() {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<Journal>();
+        service_container.add_hosted_service::<Worker>();
+        service_container.add_hosted_service::<SlowWorker>();
+        let host = Host::new(&service_container).with_shutdown_timeout(Duration::from_secs(5));
+        let journal = get_instance::<Journal>(host.service_provider()).expect("Cannot get journal");
+
+        host.start().await.expect("Cannot start host");
+        let result = host.stop().await;
+
+        // The slow worker is abandoned, the other services are still stopped
+        assert!(matches!(result, Err(Error::ShutdownTimeout { .. })));
+        assert_eq!(
+            journal.entries(),
+            ["Worker started", "SlowWorker started", "Worker stopped"]
+        );
+    }
+}
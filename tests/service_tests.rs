@@ -1,153 +1,1903 @@
+#![allow(clippy::bool_assert_comparison)]
+
 mod derive_service_tests;
 
-use shive::{
-    create_resolver,
-    service::{get_instance, get_trait_instance, ServiceProvider, ServiceResolver},
-};
-use shive::{service::Service, service_container::ServiceContainer};
-use std::sync::Arc;
+use shive::{
+    condition::Condition,
+    decorator::Decorator,
+    dispose::Dispose,
+    error::Error,
+    factory::{get_factory, get_trait_factory},
+    generic_service::{
+        GenericService, GenericTraitService, get_generic_instance, get_generic_trait_instance,
+    },
+    lazy::{get_lazy_instance, get_lazy_trait_instance},
+    options::{Options, Validate},
+    registration_policy::RegistrationPolicy,
+    service::Service,
+    service_container::ServiceContainer,
+    service_dependency::ServiceDependency,
+    service_key::ServiceKey,
+    service_module::{ModuleDependency, ServiceModule},
+    validation::ValidationError,
+};
+use shive::{
+    create_resolver,
+    service::{
+        get_all_trait_instances, get_instance, get_keyed_instance, get_keyed_trait_instance,
+        get_trait_instance, try_get_instance, try_get_keyed_instance, try_get_trait_instance,
+        ServiceProvider, ServiceResolver,
+    },
+};
+use std::sync::{
+    Arc, Condvar, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+pub trait TestTrait: Sync + Send + 'static {
+    fn is_trait_ok(&self) -> bool;
+
+    fn describe(&self) -> String {
+        "service".to_string()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TestType;
+
+impl Service for TestType {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self)
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+impl TestType {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn is_ok(&self) -> bool {
+        true
+    }
+}
+
+impl TestType {
+    pub fn test() {}
+}
+
+impl TestTrait for TestType {
+    fn is_trait_ok(&self) -> bool {
+        true
+    }
+}
+
+pub struct TestTypeCaller {
+    test_type: Arc<TestType>,
+}
+
+impl Service for TestTypeCaller {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        vec![ServiceDependency::required::<TestType>()]
+    }
+}
+
+impl TestTypeCaller {
+    pub fn is_call_ok(&self) -> bool {
+        self.test_type.is_ok()
+    }
+}
+pub struct TestTraitCaller {
+    test_type: Arc<dyn TestTrait>,
+}
+
+impl Service for TestTraitCaller {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            test_type: get_trait_instance::<dyn TestTrait>(service_provider)
+                .expect("Cannot get TestTrait"),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        vec![ServiceDependency::required::<dyn TestTrait>()]
+    }
+}
+
+pub struct TestCycleFirst;
+
+impl Service for TestCycleFirst {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Self::try_init(service_provider).expect("Cannot create TestCycleFirst")
+    }
+
+    fn try_init(service_provider: &dyn ServiceProvider) -> Result<Arc<dyn Service>, Error>
+    where
+        Self: Sized,
+    {
+        get_instance::<TestCycleSecond>(service_provider)?;
+        Ok(Arc::new(Self))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        vec![ServiceDependency::required::<TestCycleSecond>()]
+    }
+}
+
+pub struct TestCycleSecond;
+
+impl Service for TestCycleSecond {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Self::try_init(service_provider).expect("Cannot create TestCycleSecond")
+    }
+
+    fn try_init(service_provider: &dyn ServiceProvider) -> Result<Arc<dyn Service>, Error>
+    where
+        Self: Sized,
+    {
+        get_instance::<TestCycleFirst>(service_provider)?;
+        Ok(Arc::new(Self))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        vec![ServiceDependency::required::<TestCycleFirst>()]
+    }
+}
+
+/// Meeting point of two threads, passed once both threads arrived.
+pub struct TestRendezvous {
+    arrived: Mutex<usize>,
+    all_arrived: Condvar,
+}
+
+impl TestRendezvous {
+    fn arrive(&self) {
+        let mut arrived = self.arrived.lock().unwrap();
+        *arrived += 1;
+        self.all_arrived.notify_all();
+
+        while *arrived < 2 {
+            arrived = self.all_arrived.wait(arrived).unwrap();
+        }
+    }
+}
+
+impl Service for TestRendezvous {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            arrived: Mutex::new(0),
+            all_arrived: Condvar::new(),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestDeadlockFirst;
+
+impl Service for TestDeadlockFirst {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Self::try_init(service_provider).expect("Cannot create TestDeadlockFirst")
+    }
+
+    fn try_init(service_provider: &dyn ServiceProvider) -> Result<Arc<dyn Service>, Error>
+    where
+        Self: Sized,
+    {
+        // Both threads hold the init lock of their singleton before resolving the other one
+        get_instance::<TestRendezvous>(service_provider)?.arrive();
+        get_instance::<TestDeadlockSecond>(service_provider)?;
+        Ok(Arc::new(Self))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestDeadlockSecond;
+
+impl Service for TestDeadlockSecond {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Self::try_init(service_provider).expect("Cannot create TestDeadlockSecond")
+    }
+
+    fn try_init(service_provider: &dyn ServiceProvider) -> Result<Arc<dyn Service>, Error>
+    where
+        Self: Sized,
+    {
+        // Both threads hold the init lock of their singleton before resolving the other one
+        get_instance::<TestRendezvous>(service_provider)?.arrive();
+        get_instance::<TestDeadlockFirst>(service_provider)?;
+        Ok(Arc::new(Self))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestCycleCaller {
+    callee: Result<Arc<TestCycleCallee>, Error>,
+}
+
+impl Service for TestCycleCaller {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            callee: get_instance::<TestCycleCallee>(service_provider),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+impl TestTrait for TestCycleCaller {
+    fn is_trait_ok(&self) -> bool {
+        self.callee.is_ok()
+    }
+
+    fn describe(&self) -> String {
+        match &self.callee {
+            Err(Error::Cycle { path }) => path.join(" -> "),
+            _ => "service".to_string(),
+        }
+    }
+}
+
+pub struct TestCycleCallee {
+    caller: Result<Arc<dyn TestTrait>, Error>,
+}
+
+impl Service for TestCycleCallee {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            caller: get_trait_instance::<dyn TestTrait>(service_provider),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestCoreModule;
+
+impl ServiceModule for TestCoreModule {
+    fn register(service_container: &mut ServiceContainer) {
+        service_container.add_singleton::<TestType>();
+    }
+}
+
+pub struct TestCallerModule;
+
+impl ServiceModule for TestCallerModule {
+    fn register(service_container: &mut ServiceContainer) {
+        service_container.add_transient::<TestTypeCaller>();
+    }
+
+    fn dependencies() -> Vec<ModuleDependency> {
+        vec![ModuleDependency::of::<TestCoreModule>()]
+    }
+}
+
+pub struct TestCycleModule;
+
+impl ServiceModule for TestCycleModule {
+    fn register(_: &mut ServiceContainer) {}
+
+    fn dependencies() -> Vec<ModuleDependency> {
+        vec![ModuleDependency::of::<TestCycleModule>()]
+    }
+}
+
+pub struct TestDatabaseConfig {
+    pub url: String,
+    pub pool_size: u32,
+}
+
+impl Validate for TestDatabaseConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.pool_size == 0 {
+            return Err("pool_size must be positive".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl TestTraitCaller {
+    pub fn is_call_trait_ok(&self) -> bool {
+        self.test_type.is_trait_ok()
+    }
+}
+
+pub struct TestConfigured {
+    connection_string: String,
+    test_type: Arc<TestType>,
+}
+
+impl Service for TestConfigured {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            connection_string: String::new(),
+            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        vec![ServiceDependency::required::<TestType>()]
+    }
+}
+
+impl TestTrait for TestConfigured {
+    fn is_trait_ok(&self) -> bool {
+        !self.connection_string.is_empty() && self.test_type.is_ok()
+    }
+}
+
+pub struct TestGeneric<T: 'static> {
+    marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> Service for TestGeneric<T> {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestGenericDefinition;
+
+impl<T: 'static> GenericService for TestGeneric<T> {
+    type Definition = TestGenericDefinition;
+}
+
+pub trait TestGenericTrait<T>: Send + Sync + 'static {
+    fn type_name(&self) -> &'static str;
+}
+
+impl<T: 'static> TestGenericTrait<T> for TestGeneric<T> {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+pub struct TestGenericTraitDefinition;
+
+impl<T: 'static> GenericTraitService for dyn TestGenericTrait<T> {
+    type Definition = TestGenericTraitDefinition;
+    type Implementation = TestGeneric<T>;
+
+    fn resolver() -> ServiceResolver<Self> {
+        create_resolver!(dyn TestGenericTrait<T>, TestGeneric<T>)
+    }
+}
+
+pub struct TestGenericCaller {
+    generic: Arc<TestGeneric<TestType>>,
+    generic_trait: Arc<dyn TestGenericTrait<TestTypeCaller>>,
+}
+
+impl Service for TestGenericCaller {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            generic: get_instance::<TestGeneric<TestType>>(service_provider)
+                .expect("Cannot get service"),
+            generic_trait: get_trait_instance::<dyn TestGenericTrait<TestTypeCaller>>(
+                service_provider,
+            )
+            .expect("Cannot get service"),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn dependencies() -> Vec<ServiceDependency>
+    where
+        Self: Sized,
+    {
+        vec![
+            ServiceDependency::generic::<TestGeneric<TestType>>(),
+            ServiceDependency::generic_trait::<dyn TestGenericTrait<TestTypeCaller>>(),
+        ]
+    }
+}
+
+pub struct TestDecorator {
+    inner: Arc<dyn TestTrait>,
+    name: &'static str,
+}
+
+impl TestTrait for TestDecorator {
+    fn is_trait_ok(&self) -> bool {
+        self.inner.is_trait_ok()
+    }
+
+    fn describe(&self) -> String {
+        format!("{}({})", self.name, self.inner.describe())
+    }
+}
+
+pub struct TestCacheDecorator;
+
+impl Decorator<dyn TestTrait> for TestCacheDecorator {
+    fn decorate(inner: Arc<dyn TestTrait>, _: &dyn ServiceProvider) -> Arc<dyn TestTrait> {
+        Arc::new(TestDecorator {
+            inner,
+            name: "cache",
+        })
+    }
+}
+
+pub struct TestRetryDecorator;
+
+impl Decorator<dyn TestTrait> for TestRetryDecorator {
+    fn decorate(
+        inner: Arc<dyn TestTrait>,
+        service_provider: &dyn ServiceProvider,
+    ) -> Arc<dyn TestTrait> {
+        get_instance::<TestType>(service_provider).expect("Cannot get TestType");
+
+        Arc::new(TestDecorator {
+            inner,
+            name: "retry",
+        })
+    }
+}
+
+pub struct TestDisposeLog {
+    names: Mutex<Vec<&'static str>>,
+}
+
+impl TestDisposeLog {
+    pub fn names(&self) -> Vec<&'static str> {
+        self.names.lock().unwrap().clone()
+    }
+}
+
+impl Service for TestDisposeLog {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            names: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestDisposable {
+    name: &'static str,
+    log: Arc<TestDisposeLog>,
+}
+
+impl Dispose for TestDisposable {
+    fn dispose(&self) {
+        self.log.names.lock().unwrap().push(self.name);
+    }
+}
+
+pub struct TestDisposableFirst(TestDisposable);
+
+impl Service for TestDisposableFirst {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self(TestDisposable {
+            name: "first",
+            log: get_instance::<TestDisposeLog>(service_provider).expect("Cannot get log"),
+        }))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn as_disposable(self: Arc<Self>) -> Option<Arc<dyn Dispose>> {
+        Some(self)
+    }
+}
+
+impl Dispose for TestDisposableFirst {
+    fn dispose(&self) {
+        self.0.dispose();
+    }
+}
+
+impl TestTrait for TestDisposableFirst {
+    fn is_trait_ok(&self) -> bool {
+        true
+    }
+}
+
+pub struct TestDisposableSecond(TestDisposable);
+
+impl Service for TestDisposableSecond {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        get_instance::<TestDisposableFirst>(service_provider).expect("Cannot get first");
+
+        Arc::new(Self(TestDisposable {
+            name: "second",
+            log: get_instance::<TestDisposeLog>(service_provider).expect("Cannot get log"),
+        }))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn as_disposable(self: Arc<Self>) -> Option<Arc<dyn Dispose>> {
+        Some(self)
+    }
+}
+
+impl Dispose for TestDisposableSecond {
+    fn dispose(&self) {
+        self.0.dispose();
+    }
+}
+
+pub struct TestInitCount {
+    count: AtomicUsize,
+}
+
+impl Service for TestInitCount {
+    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Arc::new(Self {
+            count: AtomicUsize::new(0),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestFallibleService;
+
+impl Service for TestFallibleService {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        Self::try_init(service_provider).expect("Cannot create TestFallibleService")
+    }
+
+    fn try_init(service_provider: &dyn ServiceProvider) -> Result<Arc<dyn Service>, Error>
+    where
+        Self: Sized,
+    {
+        let init_count = get_instance::<TestInitCount>(service_provider)?;
+
+        // The first initialization fails
+        if init_count.count.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(Error::FactoryFailed {
+                type_name: std::any::type_name::<Self>().to_string(),
+                source: Arc::new(std::io::Error::other("Connection refused")),
+            });
+        }
+
+        Ok(Arc::new(Self))
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+pub struct TestSlowService {
+    test_type: Arc<TestType>,
+}
+
+impl Service for TestSlowService {
+    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
+    where
+        Self: Sized,
+    {
+        let init_count =
+            get_instance::<TestInitCount>(service_provider).expect("Cannot get TestInitCount");
+        init_count.count.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        Arc::new(Self {
+            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+        })
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+#[test]
+fn get_instance_singleton_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    let service_provider = service_container.build();
+    let service = get_instance::<TestType>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(service.is_ok(), true);
+}
+
+#[test]
+fn get_instance_unmanaged_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestType>(TestType::new());
+    let service_provider = service_container.build();
+    let service = get_instance::<TestType>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(service.is_ok(), true);
+}
+
+#[test]
+fn get_instance_scoped_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_scoped::<TestType>();
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let service: Arc<TestType> =
+        get_instance::<TestType>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(service.is_ok(), true);
+}
+
+#[test]
+fn get_instance_transient_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_transient::<TestType>();
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let service: Arc<TestType> =
+        get_instance::<TestType>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(service.is_ok(), true);
+}
+
+#[test]
+fn get_instance_scoped_same_instance_in_scope() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_scoped::<TestType>();
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+    let other_scope = root_provider.create_scope();
+    let first = get_instance::<TestType>(&scope).expect("Cannot get service");
+    let second = get_instance::<TestType>(&scope).expect("Cannot get service");
+    let other = get_instance::<TestType>(&other_scope).expect("Cannot get service");
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(!Arc::ptr_eq(&first, &other));
+}
+
+#[test]
+fn get_instance_singleton_not_found() {
+    let service_container = ServiceContainer::new();
+    let service_provider = service_container.build();
+    let service = get_instance::<TestType>(&service_provider);
+
+    assert_eq!(service.is_err(), true);
+}
+
+#[test]
+fn get_instance_generic_instantiations_are_distinct() {
+    let mut service_container = ServiceContainer::new();
+    let registered_name = {
+        struct Local;
+        service_container.add_singleton::<TestGeneric<Local>>();
+        std::any::type_name::<TestGeneric<Local>>()
+    };
+    let service_provider = service_container.build();
+
+    // Both local types have the same type name but are distinct types
+    struct Local;
+    assert_eq!(std::any::type_name::<TestGeneric<Local>>(), registered_name);
+    assert!(get_instance::<TestGeneric<Local>>(&service_provider).is_err());
+}
+
+#[test]
+fn get_instance_singleton_with_factory_ok() {
+    let connection_string = "postgres://localhost".to_string();
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container.add_singleton_with(move |service_provider| TestConfigured {
+        connection_string: connection_string.clone(),
+        test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+    });
+    let service_provider = service_container.build();
+    let service = get_instance::<TestConfigured>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(service.connection_string, "postgres://localhost");
+    assert!(service.is_trait_ok());
+}
+
+#[test]
+fn validate_factory_dependencies_ok() {
+    // The factory does not resolve the dependencies of the init function
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton_with(|_| TestConfigured {
+        connection_string: "postgres://localhost".to_string(),
+        test_type: Arc::new(TestType::new()),
+    });
+
+    assert!(service_container.validate().is_ok());
+    assert!(
+        service_container.graph().services[0]
+            .dependencies
+            .is_empty()
+    );
+}
+
+#[test]
+fn get_instance_trait_scoped_with_factory_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_transient::<TestType>();
+    service_container.add_trait_scoped_with::<dyn TestTrait, _, _>(
+        create_resolver!(dyn TestTrait, TestConfigured),
+        |service_provider| TestConfigured {
+            connection_string: "redis://localhost".to_string(),
+            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+        },
+    );
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let service =
+        get_trait_instance::<dyn TestTrait>(&service_provider).expect("Cannot get service");
+
+    assert!(service.is_trait_ok());
+}
+
+#[test]
+fn get_instance_singleton_try_with_factory_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container.add_singleton_try_with(|service_provider| {
+        Ok::<_, Error>(TestConfigured {
+            connection_string: "postgres://localhost".to_string(),
+            test_type: get_instance::<TestType>(service_provider)?,
+        })
+    });
+    let service_provider = service_container.build();
+    let service = get_instance::<TestConfigured>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(service.connection_string, "postgres://localhost");
+    assert!(service.is_trait_ok());
+}
+
+#[test]
+fn get_instance_trait_scoped_try_with_factory_failed() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_trait_scoped_try_with::<dyn TestTrait, TestConfigured, _, _>(
+        create_resolver!(dyn TestTrait, TestConfigured),
+        |_| Err(std::io::Error::other("Connection refused")),
+    );
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let result = get_trait_instance::<dyn TestTrait>(&service_provider);
+
+    match result {
+        Err(error @ Error::FactoryFailed { .. }) => {
+            assert_eq!(
+                error.to_string(),
+                format!("cannot create {}", std::any::type_name::<TestConfigured>())
+            );
+            assert_eq!(
+                std::error::Error::source(&error).map(ToString::to_string),
+                Some("Connection refused".to_string())
+            );
+        }
+        _ => panic!("The factory error is not returned"),
+    }
+}
+
+#[test]
+fn get_keyed_instance_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_keyed_unmanaged(
+        "primary",
+        TestConfigured {
+            connection_string: "primary".to_string(),
+            test_type: Arc::new(TestType),
+        },
+    );
+    service_container.add_keyed_unmanaged(
+        "replica",
+        TestConfigured {
+            connection_string: "replica".to_string(),
+            test_type: Arc::new(TestType),
+        },
+    );
+    let service_provider = service_container.build();
+    let primary = get_keyed_instance::<TestConfigured>(&service_provider, "primary")
+        .expect("Cannot get service");
+    let replica = get_keyed_instance::<TestConfigured>(&service_provider, "replica")
+        .expect("Cannot get service");
+
+    assert_eq!(primary.connection_string, "primary");
+    assert_eq!(replica.connection_string, "replica");
+    assert!(get_instance::<TestConfigured>(&service_provider).is_err());
+}
+
+#[test]
+fn get_keyed_trait_instance_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_keyed_trait_singleton::<dyn TestTrait, TestType>(
+        "primary",
+        create_resolver!(dyn TestTrait, TestType),
+    );
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let service = get_keyed_trait_instance::<dyn TestTrait>(&service_provider, "primary")
+        .expect("Cannot get service");
+
+    assert!(service.is_trait_ok());
+    assert!(get_keyed_trait_instance::<dyn TestTrait>(&service_provider, "replica").is_err());
+    assert!(get_trait_instance::<dyn TestTrait>(&service_provider).is_err());
+}
+
+#[test]
+fn get_all_trait_instances_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.add_trait_transient_with::<dyn TestTrait, _, _>(
+        create_resolver!(dyn TestTrait, TestConfigured),
+        |service_provider| TestConfigured {
+            connection_string: "transient".to_string(),
+            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+        },
+    );
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let first =
+        get_all_trait_instances::<dyn TestTrait>(&service_provider).expect("Cannot get services");
+    let second =
+        get_all_trait_instances::<dyn TestTrait>(&service_provider).expect("Cannot get services");
+
+    assert_eq!(first.len(), 2);
+    assert!(first.iter().all(|service| service.is_trait_ok()));
+    // The singleton is shared, the transient is created for each call
+    assert!(Arc::ptr_eq(&first[0], &second[0]));
+    assert!(!Arc::ptr_eq(&first[1], &second[1]));
+    // The last registration is resolved for a single instance
+    let last = get_trait_instance::<dyn TestTrait>(&service_provider).expect("Cannot get service");
+    assert!(!Arc::ptr_eq(&last, &first[1]));
+}
+
+#[test]
+fn get_all_trait_instances_empty() {
+    let service_container = ServiceContainer::new();
+    let service_provider = service_container.build();
+    let services =
+        get_all_trait_instances::<dyn TestTrait>(&service_provider).expect("Cannot get services");
+
+    assert!(services.is_empty());
+}
+
+#[test]
+fn get_generic_instance_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_open_scoped::<TestGenericDefinition>();
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+    let first = get_generic_instance::<TestGeneric<TestType>>(&scope).expect("Cannot get service");
+    let second = get_generic_instance::<TestGeneric<TestType>>(&scope).expect("Cannot get service");
+    let other_scope = root_provider.create_scope();
+    let other_scoped =
+        get_generic_instance::<TestGeneric<TestType>>(&other_scope).expect("Cannot get service");
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(!Arc::ptr_eq(&first, &other_scoped));
+    assert!(get_generic_instance::<TestGeneric<TestTypeCaller>>(&scope).is_ok());
+}
+
+#[test]
+fn get_generic_instance_not_registered() {
+    let service_container = ServiceContainer::new();
+    let service_provider = service_container.build();
+
+    assert!(get_generic_instance::<TestGeneric<TestType>>(&service_provider).is_err());
+}
+
+#[test]
+fn get_generic_trait_instance_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_open_singleton::<TestGenericTraitDefinition>();
+    let service_provider = service_container.build();
+    let first = get_generic_trait_instance::<dyn TestGenericTrait<TestType>>(&service_provider)
+        .expect("Cannot get service");
+    let second = get_generic_trait_instance::<dyn TestGenericTrait<TestType>>(&service_provider)
+        .expect("Cannot get service");
+    let other =
+        get_generic_trait_instance::<dyn TestGenericTrait<TestTypeCaller>>(&service_provider)
+            .expect("Cannot get service");
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(first.type_name().ends_with("TestType"));
+    assert!(other.type_name().ends_with("TestTypeCaller"));
+}
+
+#[test]
+fn get_instance_declared_generic_dependencies_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_open_singleton::<TestGenericDefinition>();
+    service_container.add_open_singleton::<TestGenericTraitDefinition>();
+    service_container.add_singleton::<TestGenericCaller>();
+    let service_provider = service_container.build();
+    let caller = get_instance::<TestGenericCaller>(&service_provider).expect("Cannot get service");
+    let generic =
+        get_instance::<TestGeneric<TestType>>(&service_provider).expect("Cannot get service");
+
+    assert!(service_container.validate().is_ok());
+    assert!(Arc::ptr_eq(&caller.generic, &generic));
+    assert!(caller.generic_trait.type_name().ends_with("TestTypeCaller"));
+}
+
+#[test]
+fn validate_declared_generic_dependencies_missing() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_open_scoped::<TestGenericDefinition>();
+    service_container.add_singleton::<TestGenericCaller>();
+
+    assert!(service_container.validate().is_err());
+}
+
+#[test]
+fn get_decorated_trait_instance_singleton_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.decorate::<dyn TestTrait, TestCacheDecorator>();
+    service_container.decorate::<dyn TestTrait, TestRetryDecorator>();
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+    let first = get_trait_instance::<dyn TestTrait>(&scope).expect("Cannot get service");
+    let second = get_trait_instance::<dyn TestTrait>(&scope).expect("Cannot get service");
+
+    assert_eq!(first.describe(), "retry(cache(service))");
+    assert!(first.is_trait_ok());
+    assert!(Arc::ptr_eq(&first, &second));
+}
+
+#[test]
+fn get_decorated_trait_instance_transient_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container
+        .add_trait_transient::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.decorate::<dyn TestTrait, TestCacheDecorator>();
+    service_container.add_trait_unmanaged::<dyn TestTrait, TestType>(
+        create_resolver!(dyn TestTrait, TestType),
+        TestType::new(),
+    );
+    service_container.add_scoped::<TestTraitCaller>();
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+    let services = get_all_trait_instances::<dyn TestTrait>(&scope).expect("Cannot get services");
+    let other = get_all_trait_instances::<dyn TestTrait>(&scope).expect("Cannot get services");
+    let caller = get_instance::<TestTraitCaller>(&scope).expect("Cannot get service");
+
+    assert_eq!(services[0].describe(), "cache(service)");
+    assert!(!Arc::ptr_eq(&services[0], &other[0]));
+    // Registrations declared after the decorator are not decorated
+    assert_eq!(services[1].describe(), "service");
+    assert_eq!(caller.test_type.describe(), "service");
+}
+
+#[test]
+fn get_decorated_trait_instance_unmanaged_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_trait_unmanaged::<dyn TestTrait, TestType>(
+        create_resolver!(dyn TestTrait, TestType),
+        TestType::new(),
+    );
+    service_container.decorate::<dyn TestTrait, TestCacheDecorator>();
+    service_container.add_singleton::<TestTraitCaller>();
+    let service_provider = service_container.build();
+    let caller = get_instance::<TestTraitCaller>(&service_provider).expect("Cannot get service");
+    let service =
+        get_trait_instance::<dyn TestTrait>(&service_provider).expect("Cannot get service");
+
+    assert_eq!(caller.test_type.describe(), "cache(service)");
+    assert!(Arc::ptr_eq(&caller.test_type, &service));
+}
+
+#[test]
+fn try_add_keeps_existing_registration() {
+    let mut service_container = ServiceContainer::new();
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+
+    assert!(
+        !service_container.try_add_trait_transient::<dyn TestTrait, TestType>(create_resolver!(
+            dyn TestTrait,
+            TestType
+        ))
+    );
+    assert!(service_container.try_add_scoped::<TestType>());
+    assert!(!service_container.try_add_unmanaged::<TestType>(TestType::new()));
+
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+    let first = get_trait_instance::<dyn TestTrait>(&scope).expect("Cannot get service");
+    let second = get_trait_instance::<dyn TestTrait>(&root_provider).expect("Cannot get service");
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(get_instance::<TestType>(&scope).is_ok());
+    assert!(get_instance::<TestType>(&root_provider).is_err());
+}
+
+#[test]
+fn replace_overrides_registrations() {
+    let mut service_container = ServiceContainer::new();
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container
+        .add_trait_scoped::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.add_unmanaged::<TestType>(TestType::new());
+    service_container.replace_trait_transient::<dyn TestTrait, TestType>(create_resolver!(
+        dyn TestTrait,
+        TestType
+    ));
+    service_container.replace_singleton::<TestType>();
+
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+    let services = get_all_trait_instances::<dyn TestTrait>(&scope).expect("Cannot get services");
+    let other = get_trait_instance::<dyn TestTrait>(&scope).expect("Cannot get service");
+
+    assert_eq!(services.len(), 1);
+    assert!(!Arc::ptr_eq(&services[0], &other));
+    assert!(get_instance::<TestType>(&root_provider).is_ok());
+}
+
+#[test]
+fn remove_registrations() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestType>(TestType::new());
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+
+    assert!(service_container.remove::<TestType>());
+    assert!(!service_container.remove::<TestType>());
+    assert!(!service_container.contains::<TestType>());
+    assert!(service_container.contains::<dyn TestTrait>());
+    assert!(service_container.unmanaged_services.is_empty());
+
+    let service_provider = service_container.build();
+
+    assert!(get_instance::<TestType>(&service_provider).is_err());
+    assert!(get_trait_instance::<dyn TestTrait>(&service_provider).is_ok());
+}
+
+#[test]
+fn registration_policy_allows_other_implementations() {
+    let mut service_container = ServiceContainer::new();
+    service_container.set_registration_policy(RegistrationPolicy::Error);
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.add_trait_singleton_with::<dyn TestTrait, _, _>(
+        create_resolver!(dyn TestTrait, TestConfigured),
+        |_| TestConfigured {
+            connection_string: "policy".to_string(),
+            test_type: Arc::new(TestType),
+        },
+    );
+    service_container.add_keyed_singleton::<TestType>("primary");
+    service_container.add_singleton::<TestType>();
+    let service_provider = service_container.build();
+
+    assert_eq!(
+        get_all_trait_instances::<dyn TestTrait>(&service_provider)
+            .expect("Cannot get services")
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn registration_policy_error_fails_validation() {
+    let mut service_container = ServiceContainer::new();
+    service_container.set_registration_policy(RegistrationPolicy::Error);
+    service_container.add_singleton::<TestType>();
+    service_container.add_scoped::<TestType>();
+
+    match service_container.build_validated() {
+        Err(Error::Validation(validation_errors)) => {
+            assert_eq!(validation_errors.len(), 1);
+            assert!(matches!(
+                &validation_errors[0],
+                ValidationError::DuplicateRegistration { service, implementation }
+                    if *service == ServiceKey::of::<TestType>()
+                        && *implementation == ServiceKey::of::<TestType>()
+            ));
+        }
+        _ => panic!("Duplicate registration not reported"),
+    }
+    assert!(service_container.warnings().is_empty());
+}
+
+#[test]
+fn registration_policy_warn_reports_warnings() {
+    let mut service_container = ServiceContainer::new();
+    service_container.set_registration_policy(RegistrationPolicy::Warn);
+    service_container.add_singleton::<TestType>();
+    service_container.add_singleton::<TestType>();
+
+    assert!(service_container.validate().is_ok());
+    assert_eq!(service_container.warnings().len(), 1);
+
+    service_container.replace_singleton::<TestType>();
+    assert!(service_container.warnings().is_empty());
+}
+
+#[test]
+fn remove_keyed_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container.add_keyed_singleton::<TestType>("primary");
+
+    assert!(service_container.remove::<TestType>());
+    assert!(!service_container.contains::<TestType>());
+    assert!(service_container.remove_keyed::<TestType>("primary"));
+    assert!(!service_container.remove_keyed::<TestType>("primary"));
+    assert!(service_container.get_service_definitions().is_empty());
+}
+
+#[test]
+fn add_module_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_module::<TestCallerModule>();
+    service_container.add_module::<TestCoreModule>();
+    assert!(service_container.contains_module::<TestCoreModule>());
+    assert!(service_container.contains_module::<TestCallerModule>());
+
+    let service_definitions = service_container.get_service_definitions();
+    assert_eq!(service_definitions.len(), 2);
+    assert_eq!(*service_definitions[0].0, ServiceKey::of::<TestType>());
+    assert_eq!(
+        *service_definitions[1].0,
+        ServiceKey::of::<TestTypeCaller>()
+    );
+
+    let root_provider = service_container
+        .build_validated()
+        .expect("Invalid container");
+    let service_provider = root_provider.create_scope();
+    let service = get_instance::<TestTypeCaller>(&service_provider).expect("Cannot get service");
+    assert!(service.is_call_ok());
+}
+
+#[test]
+fn add_module_cycle_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_module::<TestCycleModule>();
+
+    let path = [
+        std::any::type_name::<TestCycleModule>(),
+        std::any::type_name::<TestCycleModule>(),
+    ];
+    let validation_errors = match service_container.validate() {
+        Err(Error::Validation(validation_errors)) => validation_errors,
+        _ => panic!("Circular module dependency is not reported"),
+    };
+    assert_eq!(validation_errors.len(), 1);
+    assert!(matches!(
+        &validation_errors[0],
+        ValidationError::CircularModuleDependency { path: error_path } if *error_path == path
+    ));
+}
 
-pub trait TestTrait: Sync + Send + 'static {
-    fn is_trait_ok(&self) -> bool;
+#[test]
+fn merge_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_transient::<TestTypeCaller>();
+
+    let mut other_container = ServiceContainer::new();
+    other_container.add_module::<TestCoreModule>();
+    other_container.add_unmanaged::<TestType>(TestType::new());
+    service_container.merge(other_container);
+
+    assert!(service_container.contains_module::<TestCoreModule>());
+    let service_definitions =
+        service_container.get_service_definitions_from_key(&ServiceKey::of::<TestType>());
+    assert_eq!(service_definitions.len(), 2);
+    assert_eq!(service_definitions[0].id, 1);
+    assert_eq!(service_definitions[1].id, 2);
+
+    let root_provider = service_container.build();
+    let service_provider = root_provider.create_scope();
+    let service = get_instance::<TestTypeCaller>(&service_provider).expect("Cannot get service");
+    assert!(service.is_call_ok());
 }
 
-#[derive(Clone)]
-pub struct TestType;
+#[test]
+fn merge_skips_installed_modules() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_module::<TestCoreModule>();
 
-impl Service for TestType {
-    fn init(_: &dyn ServiceProvider) -> Arc<dyn Service>
-    where
-        Self: Sized,
-    {
-        Arc::new(Self)
-    }
+    let mut other_container = ServiceContainer::new();
+    other_container.add_module::<TestCallerModule>();
+    service_container.merge(other_container);
 
-    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
-        self
-    }
+    assert!(service_container.contains_module::<TestCallerModule>());
+    assert_eq!(
+        service_container
+            .get_service_definitions_from_key(&ServiceKey::of::<TestType>())
+            .len(),
+        1
+    );
+    assert_eq!(
+        service_container
+            .get_service_definitions_from_key(&ServiceKey::of::<TestTypeCaller>())
+            .len(),
+        1
+    );
 }
 
-impl TestType {
-    pub fn new() -> Self {
-        Self {}
-    }
+#[test]
+fn create_child_from_cloned_container_ok() {
+    let mut parent_container = ServiceContainer::new();
+    parent_container.add_singleton::<TestTypeCaller>();
+    let mut child_container = parent_container.clone();
+    parent_container.add_singleton::<TestType>();
+    child_container.add_singleton::<TestInitCount>();
+    let parent_provider = parent_container.build();
+    let child_provider = parent_provider.create_child(&child_container);
 
-    pub fn is_ok(&self) -> bool {
-        true
-    }
+    let parent_caller =
+        get_instance::<TestTypeCaller>(&parent_provider).expect("Cannot get service");
+    let child_caller = get_instance::<TestTypeCaller>(&child_provider).expect("Cannot get service");
+
+    assert!(get_instance::<TestInitCount>(&child_provider).is_ok());
+    assert!(get_instance::<TestType>(&child_provider).is_ok());
+    assert!(Arc::ptr_eq(&parent_caller, &child_caller));
 }
 
-impl TestType {
-    pub fn test() {}
+#[test]
+fn create_child_from_decorated_clone_ok() {
+    let mut parent_container = ServiceContainer::new();
+    parent_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    let mut child_container = parent_container.clone();
+    child_container.decorate::<dyn TestTrait, TestCacheDecorator>();
+    let parent_provider = parent_container.build();
+    let child_provider = parent_provider.create_child(&child_container);
+
+    // The decorated registration is not shared with the parent anymore
+    let child_service =
+        get_trait_instance::<dyn TestTrait>(&child_provider).expect("Cannot get service");
+    let parent_service =
+        get_trait_instance::<dyn TestTrait>(&parent_provider).expect("Cannot get service");
+    let other_child_service =
+        get_trait_instance::<dyn TestTrait>(&child_provider).expect("Cannot get service");
+
+    assert_eq!(child_service.describe(), "cache(service)");
+    assert_eq!(parent_service.describe(), "service");
+    assert!(Arc::ptr_eq(&child_service, &other_child_service));
 }
 
-impl TestTrait for TestType {
-    fn is_trait_ok(&self) -> bool {
-        true
-    }
+#[test]
+fn create_child_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container
+        .add_trait_scoped::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.add_scoped::<TestTraitCaller>();
+    let root_provider = service_container.build();
+    let parent_service =
+        get_instance::<TestType>(&root_provider).expect("Cannot get parent service");
+
+    let mut child_container = ServiceContainer::new();
+    child_container.add_trait_scoped_with::<dyn TestTrait, _, _>(
+        create_resolver!(dyn TestTrait, TestConfigured),
+        |service_provider| TestConfigured {
+            connection_string: "child".to_string(),
+            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+        },
+    );
+    let child_provider = root_provider.create_child(&child_container);
+
+    // Singletons of the parent are shared
+    let child_service = get_instance::<TestType>(&child_provider).expect("Cannot get service");
+    assert!(Arc::ptr_eq(&parent_service, &child_service));
+
+    // Inherited registrations resolve the overrides of the child
+    let service_provider = child_provider.create_scope();
+    let caller =
+        get_instance::<TestTraitCaller>(&service_provider).expect("Cannot get child caller");
+    assert!(caller.is_call_trait_ok());
+    let child_trait =
+        get_trait_instance::<dyn TestTrait>(&service_provider).expect("Cannot get child trait");
+    assert!(Arc::ptr_eq(&child_trait, &caller.test_type));
+
+    // The parent is not affected by the child
+    let service_provider = root_provider.create_scope();
+    let parent_trait =
+        get_trait_instance::<dyn TestTrait>(&service_provider).expect("Cannot get parent trait");
+    assert!(!Arc::ptr_eq(&parent_trait, &child_trait));
 }
 
-pub struct TestTypeCaller {
-    test_type: Arc<TestType>,
+#[test]
+fn conditional_registrations_ok() {
+    let mut service_container = ServiceContainer::with_profile("dev");
+    service_container.add_trait_singleton_if::<dyn TestTrait, TestType>(
+        create_resolver!(dyn TestTrait, TestType),
+        Condition::profile("dev"),
+    );
+    service_container.add_conditional(Condition::profile("prod"), |service_container| {
+        service_container.add_trait_singleton_with::<dyn TestTrait, _, _>(
+            create_resolver!(dyn TestTrait, TestConfigured),
+            |service_provider| TestConfigured {
+                connection_string: "prod".to_string(),
+                test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
+            },
+        );
+    });
+    service_container.add_singleton_if::<TestType>(Condition::env_eq("CARGO_PKG_NAME", "shive"));
+    service_container
+        .add_singleton_if::<TestTypeCaller>(Condition::env("SHIVE_UNDEFINED_VARIABLE"));
+    service_container.add_scoped_if::<TestTraitCaller>(
+        Condition::predicate(|| true).and(Condition::profile("dev")),
+    );
+
+    let root_provider = service_container
+        .build_validated()
+        .expect("Invalid container");
+    let services =
+        get_all_trait_instances::<dyn TestTrait>(&root_provider).expect("Cannot get services");
+    assert_eq!(services.len(), 1);
+    assert!(get_instance::<TestType>(&root_provider).is_ok());
+    assert!(get_instance::<TestTypeCaller>(&root_provider).is_err());
+
+    let service_provider = root_provider.create_scope();
+    let caller = get_instance::<TestTraitCaller>(&service_provider).expect("Cannot get service");
+    assert!(caller.is_call_trait_ok());
+    assert_eq!(service_container.graph().services.len(), 3);
 }
 
-impl Service for TestTypeCaller {
-    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
-    where
-        Self: Sized,
-    {
-        Arc::new(Self {
-            test_type: get_instance::<TestType>(service_provider).expect("Cannot get TestType"),
-        })
-    }
+#[test]
+fn get_options_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_options(TestDatabaseConfig {
+        url: "postgres://primary".to_string(),
+        pool_size: 10,
+    });
+    service_container.add_named_options(
+        "replica",
+        TestDatabaseConfig {
+            url: "postgres://replica".to_string(),
+            pool_size: 2,
+        },
+    );
+    let service_provider = service_container
+        .build_validated()
+        .expect("Invalid container");
 
-    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
-        self
-    }
+    let options =
+        get_instance::<Options<TestDatabaseConfig>>(&service_provider).expect("Cannot get options");
+    assert_eq!(options.url, "postgres://primary");
+    assert_eq!(options.value().pool_size, 10);
+    let replica = get_keyed_instance::<Options<TestDatabaseConfig>>(&service_provider, "replica")
+        .expect("Cannot get named options");
+    assert_eq!(replica.url, "postgres://replica");
 }
 
-impl TestTypeCaller {
-    pub fn is_call_ok(&self) -> bool {
-        self.test_type.is_ok()
+#[test]
+fn validate_options_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_named_options(
+        "replica",
+        TestDatabaseConfig {
+            url: "postgres://replica".to_string(),
+            pool_size: 0,
+        },
+    );
+
+    match service_container.validate() {
+        Err(Error::Validation(errors)) => {
+            assert_eq!(errors.len(), 1);
+            assert!(matches!(
+                &errors[0],
+                ValidationError::InvalidOptions { options, message }
+                    if *options == ServiceKey::named::<Options<TestDatabaseConfig>>("replica")
+                        && message == "pool_size must be positive"
+            ));
+        }
+        _ => panic!("Options should be invalid"),
     }
 }
-pub struct TestTraitCaller {
-    test_type: Arc<dyn TestTrait>,
+
+#[test]
+fn move_providers_across_threads_ok() {
+    fn assert_send_sync<T: Send + Sync + 'static>(_: &T) {}
+
+    let root_provider = {
+        let mut service_container = ServiceContainer::new();
+        service_container.add_singleton::<TestType>();
+        service_container.add_scoped::<TestTypeCaller>();
+        service_container.build()
+    };
+    assert_send_sync(&root_provider);
+    let singleton = get_instance::<TestType>(&root_provider).expect("Cannot get singleton");
+
+    let service_provider = root_provider.create_scope();
+    assert_send_sync(&service_provider);
+    let caller = get_instance::<TestTypeCaller>(&service_provider).expect("Cannot get service");
+
+    let scope = service_provider.clone();
+    let handle = std::thread::spawn(move || {
+        get_instance::<TestTypeCaller>(&scope).expect("Cannot get service in thread")
+    });
+    let thread_caller = handle.join().expect("Thread failed");
+
+    assert!(Arc::ptr_eq(&caller, &thread_caller));
+    assert!(Arc::ptr_eq(&singleton, &thread_caller.test_type));
 }
 
-impl Service for TestTraitCaller {
-    fn init(service_provider: &dyn ServiceProvider) -> Arc<dyn Service>
-    where
-        Self: Sized,
-    {
-        Arc::new(Self {
-            test_type: get_trait_instance::<dyn TestTrait>(service_provider)
-                .expect("Cannot get TestTrait"),
-        })
-    }
+/// Resolve the service from several threads at the same time.
+fn get_instance_concurrently<T: Service>(
+    service_provider: &(dyn ServiceProvider + Sync),
+) -> Vec<Arc<T>> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| get_instance::<T>(service_provider).expect("Cannot get service"))
+            })
+            .collect();
 
-    fn as_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
-        self
-    }
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Thread failed"))
+            .collect()
+    })
 }
 
-impl TestTraitCaller {
-    pub fn is_call_trait_ok(&self) -> bool {
-        self.test_type.is_trait_ok()
-    }
+#[test]
+fn get_instance_singleton_retry_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestInitCount>();
+    service_container.add_singleton::<TestFallibleService>();
+    let root_provider = service_container.build();
+
+    // The failed singleton is not cached and is created again on the next call
+    let result = get_instance::<TestFallibleService>(&root_provider);
+    let service = get_instance::<TestFallibleService>(&root_provider).expect("Cannot get service");
+    let cached = get_instance::<TestFallibleService>(&root_provider).expect("Cannot get service");
+    let init_count = get_instance::<TestInitCount>(&root_provider).expect("Cannot get service");
+
+    assert!(matches!(result, Err(Error::FactoryFailed { .. })));
+    assert!(Arc::ptr_eq(&service, &cached));
+    assert_eq!(init_count.count.load(Ordering::SeqCst), 2);
 }
 
 #[test]
-fn get_instance_singleton_ok() {
+fn get_instance_singleton_concurrently_ok() {
     let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestInitCount>(TestInitCount {
+        count: AtomicUsize::new(0),
+    });
+    service_container.add_singleton::<TestSlowService>();
     service_container.add_singleton::<TestType>();
-    let service_provider = service_container.build();
-    let service = get_instance::<TestType>(&service_provider).expect("Cannot get service");
+    let root_provider = service_container.build();
 
-    assert_eq!(service.is_ok(), true);
+    let services = get_instance_concurrently::<TestSlowService>(&root_provider);
+    let init_count = get_instance::<TestInitCount>(&root_provider).expect("Cannot get service");
+
+    assert_eq!(init_count.count.load(Ordering::SeqCst), 1);
+    assert!(
+        services
+            .iter()
+            .all(|service| Arc::ptr_eq(service, &services[0]))
+    );
+    assert!(services[0].test_type.is_ok());
 }
 
 #[test]
-fn get_instance_unmanaged_ok() {
+fn get_instance_scoped_concurrently_ok() {
     let mut service_container = ServiceContainer::new();
-    service_container.add_unmanaged::<TestType>(TestType::new());
-    let service_provider = service_container.build();
-    let service = get_instance::<TestType>(&service_provider).expect("Cannot get service");
+    service_container.add_unmanaged::<TestInitCount>(TestInitCount {
+        count: AtomicUsize::new(0),
+    });
+    service_container.add_scoped::<TestSlowService>();
+    service_container.add_scoped::<TestType>();
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
 
-    assert_eq!(service.is_ok(), true);
+    let services = get_instance_concurrently::<TestSlowService>(&scope);
+    let init_count = get_instance::<TestInitCount>(&scope).expect("Cannot get service");
+
+    assert_eq!(init_count.count.load(Ordering::SeqCst), 1);
+    assert!(
+        services
+            .iter()
+            .all(|service| Arc::ptr_eq(service, &services[0]))
+    );
 }
 
 #[test]
-fn get_instance_scoped_ok() {
+fn get_instance_cycle_path_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_scoped::<TestCycleCallee>();
+    service_container.add_trait_transient::<dyn TestTrait, TestCycleCaller>(create_resolver!(
+        dyn TestTrait,
+        TestCycleCaller
+    ));
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+
+    // The caller cannot get the callee being created
+    let callee = get_instance::<TestCycleCallee>(&scope).expect("Cannot get service");
+    let caller = callee.caller.as_ref().expect("Cannot get caller");
+    let path = [
+        std::any::type_name::<TestCycleCallee>(),
+        std::any::type_name::<TestCycleCaller>(),
+        std::any::type_name::<TestCycleCallee>(),
+    ];
+
+    assert!(!caller.is_trait_ok());
+    assert_eq!(caller.describe(), path.join(" -> "));
+}
+
+#[test]
+fn get_instance_cycle_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestCycleFirst>();
+    service_container.add_singleton::<TestCycleSecond>();
+    let root_provider = service_container.build();
+
+    let result = get_instance::<TestCycleFirst>(&root_provider);
+    let path = [
+        std::any::type_name::<TestCycleFirst>(),
+        std::any::type_name::<TestCycleSecond>(),
+        std::any::type_name::<TestCycleFirst>(),
+    ];
+
+    assert!(matches!(result, Err(Error::Cycle { path: error_path }) if error_path == path));
+}
+
+#[test]
+fn get_instance_cycle_concurrently_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestRendezvous>();
+    service_container.add_singleton::<TestDeadlockFirst>();
+    service_container.add_singleton::<TestDeadlockSecond>();
+    let root_provider = service_container.build();
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let first_provider = root_provider.clone();
+    let first_sender = sender.clone();
+    std::thread::spawn(move || {
+        let result = get_instance::<TestDeadlockFirst>(&first_provider).map(|_| ());
+        first_sender.send(result).unwrap();
+    });
+    std::thread::spawn(move || {
+        let result = get_instance::<TestDeadlockSecond>(&root_provider).map(|_| ());
+        sender.send(result).unwrap();
+    });
+
+    // The threads waiting on each other get the cycle instead of blocking
+    for _ in 0..2 {
+        let result = receiver
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("Circular resolution is not detected");
+        assert!(
+            matches!(result, Err(Error::Cycle { path }) if path.len() == 3 && path[0] == path[2])
+        );
+    }
+}
+
+#[test]
+fn get_instance_resolution_path_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_transient::<TestCycleCaller>();
+    let root_provider = service_container.build();
+    let scope = root_provider.create_scope();
+
+    // The missing dependency is reported with the services being created
+    let caller = get_instance::<TestCycleCaller>(&scope).expect("Cannot get service");
+    let error = caller.callee.as_ref().err().expect("Cannot get error");
+    let path = [
+        std::any::type_name::<TestCycleCaller>(),
+        std::any::type_name::<TestCycleCallee>(),
+    ];
+
+    assert!(matches!(error, Error::Resolution { path: error_path, .. } if *error_path == path));
+    assert!(matches!(error.root_cause(), Error::NotRegistered { .. }));
+    assert_eq!(
+        error.to_string(),
+        format!("cannot resolve {}", path.join(" -> "))
+    );
+    assert_eq!(
+        std::error::Error::source(error).map(ToString::to_string),
+        Some(format!("{} is not registered", path[1]))
+    );
+}
+
+#[test]
+fn get_lazy_instance_scoped_ok() {
     let mut service_container = ServiceContainer::new();
     service_container.add_scoped::<TestType>();
+    service_container
+        .add_trait_scoped::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
     let root_provider = service_container.build();
     let service_provider = root_provider.create_scope();
-    let service: Arc<TestType> =
-        get_instance::<TestType>(&service_provider).expect("Cannot get service");
 
-    assert_eq!(service.is_ok(), true);
+    let lazy = get_lazy_instance::<TestType>(&service_provider);
+    assert!(!lazy.is_created());
+    assert!(lazy.is_ok());
+    assert!(lazy.is_created());
+
+    // The lazy instance is created in its scope
+    let service = get_instance::<TestType>(&service_provider).expect("Cannot get service");
+    assert!(Arc::ptr_eq(&lazy.get().expect("Cannot get lazy"), &service));
+    let other_provider = root_provider.create_scope();
+    let other_service = get_instance::<TestType>(&other_provider).expect("Cannot get service");
+    assert!(!Arc::ptr_eq(&service, &other_service));
+
+    let lazy_trait = get_lazy_trait_instance::<dyn TestTrait>(&service_provider);
+    assert!(lazy_trait.is_trait_ok());
 }
 
 #[test]
-fn get_instance_transient_ok() {
+fn get_lazy_instance_not_found_ko() {
+    let service_container = ServiceContainer::new();
+    let root_provider = service_container.build();
+
+    let lazy = get_lazy_instance::<TestType>(&root_provider);
+    assert!(lazy.get().is_err());
+    assert!(!lazy.is_created());
+}
+
+#[test]
+fn get_factory_ok() {
     let mut service_container = ServiceContainer::new();
-    service_container.add_transient::<TestType>();
+    service_container.add_singleton::<TestType>();
+    service_container.add_scoped::<TestTypeCaller>();
+    service_container
+        .add_trait_singleton::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
     let root_provider = service_container.build();
     let service_provider = root_provider.create_scope();
-    let service: Arc<TestType> =
-        get_instance::<TestType>(&service_provider).expect("Cannot get service");
 
-    assert_eq!(service.is_ok(), true);
+    // Each call creates a new instance with the dependencies of the provider
+    let factory = get_factory::<TestTypeCaller>(&service_provider);
+    let first = factory.create().expect("Cannot create service");
+    let second = factory.create().expect("Cannot create service");
+    assert!(!Arc::ptr_eq(&first, &second));
+    assert!(Arc::ptr_eq(&first.test_type, &second.test_type));
+
+    let trait_factory = get_trait_factory::<dyn TestTrait>(&service_provider);
+    let first = trait_factory.create().expect("Cannot create trait service");
+    let second = trait_factory.create().expect("Cannot create trait service");
+    assert!(first.is_trait_ok());
+    assert!(!Arc::ptr_eq(&first, &second));
 }
 
 #[test]
-fn get_instance_singleton_not_found() {
-    let service_container = ServiceContainer::new();
-    let service_provider = service_container.build();
-    let service = get_instance::<TestType>(&service_provider);
+fn get_factory_unmanaged_ko() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestType>(TestType::new());
+    let root_provider = service_container.build();
 
-    assert_eq!(service.is_err(), true);
+    let factory = get_factory::<TestType>(&root_provider);
+    assert!(matches!(
+        factory.create(),
+        Err(Error::LifetimeMismatch { .. })
+    ));
+    let missing_factory = get_factory::<TestTypeCaller>(&root_provider);
+    assert!(missing_factory.create().is_err());
+}
+
+#[test]
+fn try_get_instance_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container.add_transient::<TestTypeCaller>();
+    let root_provider = service_container.build();
+
+    let service = try_get_instance::<TestType>(&root_provider).expect("Cannot get service");
+    assert!(service.is_some_and(|service| service.is_ok()));
+    let missing = try_get_trait_instance::<dyn TestTrait>(&root_provider)
+        .expect("Cannot get missing service");
+    assert!(missing.is_none());
+    let missing_keyed = try_get_keyed_instance::<TestType>(&root_provider, "primary")
+        .expect("Cannot get missing keyed service");
+    assert!(missing_keyed.is_none());
+
+    // A registered service which cannot be created is an error
+    assert!(try_get_instance::<TestTypeCaller>(&root_provider).is_err());
+}
+
+#[test]
+fn build_validated_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestTypeCaller>();
+    service_container.add_singleton::<TestType>();
+    service_container.add_scoped::<TestTraitCaller>();
+    service_container
+        .add_trait_transient::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    let service_provider = service_container
+        .build_validated()
+        .expect("Cannot validate container");
+
+    assert!(get_instance::<TestTypeCaller>(&service_provider).is_ok());
+}
+
+#[test]
+fn build_validated_reports_all_errors() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_scoped::<TestTypeCaller>();
+    service_container.add_singleton::<TestTraitCaller>();
+    service_container
+        .add_trait_scoped::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.add_transient::<TestCycleFirst>();
+    service_container.add_scoped::<TestCycleSecond>();
+
+    let validation_errors = match service_container.build_validated() {
+        Err(Error::Validation(validation_errors)) => validation_errors,
+        _ => panic!("The container should not be valid"),
+    };
+
+    assert_eq!(validation_errors.len(), 3);
+    assert!(matches!(
+        &validation_errors[0],
+        ValidationError::MissingDependency { service, dependency }
+            if *service == ServiceKey::of::<TestTypeCaller>() && *dependency == ServiceKey::of::<TestType>()
+    ));
+    assert!(matches!(
+        &validation_errors[1],
+        ValidationError::CaptiveDependency { service, dependency, .. }
+            if *service == ServiceKey::of::<TestTraitCaller>() && *dependency == ServiceKey::of::<dyn TestTrait>()
+    ));
+    assert!(matches!(
+        &validation_errors[2],
+        ValidationError::CircularDependency { path }
+            if *path == vec![
+                ServiceKey::of::<TestCycleFirst>(),
+                ServiceKey::of::<TestCycleSecond>(),
+                ServiceKey::of::<TestCycleFirst>(),
+            ]
+    ));
+}
+
+#[test]
+fn export_graph() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_singleton::<TestType>();
+    service_container
+        .add_trait_scoped::<dyn TestTrait, TestType>(create_resolver!(dyn TestTrait, TestType));
+    service_container.add_transient::<TestTypeCaller>();
+    service_container.add_scoped::<TestCycleFirst>();
+    let graph = service_container.graph();
+
+    assert_eq!(graph.services.len(), 4);
+    assert_eq!(graph.services[2].dependencies[0].resolved, vec![0]);
+    assert!(graph.services[3].dependencies[0].resolved.is_empty());
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph services {"));
+    assert!(dot.contains(
+        "s1 [label=\"dyn service_tests::TestTrait\\nservice_tests::TestType\\nScoped\"];"
+    ));
+    assert!(dot.contains("s2 -> s0;"));
+    assert!(dot.contains("s3 -> m3_0;"));
+
+    let mermaid = graph.to_mermaid();
+    assert!(mermaid.starts_with("flowchart LR"));
+    assert!(mermaid.contains("s0[\"service_tests::TestType<br/>Singleton\"]"));
+    assert!(mermaid.contains("s2 --> s0"));
+
+    let json = graph.to_json();
+    assert!(json.contains(
+        r#"{ "key": "service_tests::TestType", "name": null, "kind": "Required", "resolved": [0] }"#
+    ));
+    assert!(json.contains(r#""key": "dyn service_tests::TestTrait","#));
+    assert!(json.contains(r#""implementation": "service_tests::TestType","#));
+    assert!(json.contains(r#""lifetime": "Transient","#));
 }
 
 #[test]
@@ -321,3 +2071,92 @@ fn get_instance_trait_transient_from_singleton_should_panic() {
 
     assert_eq!(service.is_call_trait_ok(), true);
 }
+
+#[test]
+fn dispose_scope_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestDisposeLog>(TestDisposeLog {
+        names: Mutex::new(Vec::new()),
+    });
+    service_container.add_scoped::<TestDisposableFirst>();
+    service_container.add_transient::<TestDisposableSecond>();
+    let root_provider = service_container.build();
+    let log = get_instance::<TestDisposeLog>(&root_provider).expect("Cannot get service");
+
+    let scope = root_provider.create_scope();
+    get_instance::<TestDisposableSecond>(&scope).expect("Cannot get service");
+    get_instance::<TestDisposableSecond>(&scope).expect("Cannot get service");
+    assert!(log.names().is_empty());
+
+    // Dropping the scope disposes its instances in reverse creation order
+    drop(scope);
+    assert_eq!(log.names(), vec!["second", "second", "first"]);
+}
+
+#[test]
+fn dispose_factory_instances_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestDisposeLog>(TestDisposeLog {
+        names: Mutex::new(Vec::new()),
+    });
+    service_container.add_scoped::<TestDisposableFirst>();
+    service_container.add_transient::<TestDisposableSecond>();
+    let root_provider = service_container.build();
+    let log = get_instance::<TestDisposeLog>(&root_provider).expect("Cannot get service");
+
+    let scope = root_provider.create_scope();
+    let factory = get_factory::<TestDisposableSecond>(&scope);
+    factory.create().expect("Cannot create service");
+    factory.create().expect("Cannot create service");
+
+    scope.dispose();
+    assert_eq!(log.names(), vec!["second", "second", "first"]);
+}
+
+#[test]
+fn dispose_decorated_instance_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestDisposeLog>(TestDisposeLog {
+        names: Mutex::new(Vec::new()),
+    });
+    service_container.add_trait_scoped::<dyn TestTrait, TestDisposableFirst>(create_resolver!(
+        dyn TestTrait,
+        TestDisposableFirst
+    ));
+    service_container.decorate::<dyn TestTrait, TestCacheDecorator>();
+    service_container.decorate::<dyn TestTrait, TestRetryDecorator>();
+    service_container.add_singleton::<TestType>();
+    let root_provider = service_container.build();
+    let log = get_instance::<TestDisposeLog>(&root_provider).expect("Cannot get service");
+
+    let scope = root_provider.create_scope();
+    let service = get_trait_instance::<dyn TestTrait>(&scope).expect("Cannot get service");
+    assert!(service.is_trait_ok());
+
+    scope.dispose();
+    assert_eq!(log.names(), vec!["first"]);
+}
+
+#[test]
+fn dispose_singleton_ok() {
+    let mut service_container = ServiceContainer::new();
+    service_container.add_unmanaged::<TestDisposeLog>(TestDisposeLog {
+        names: Mutex::new(Vec::new()),
+    });
+    service_container.add_singleton::<TestDisposableFirst>();
+    service_container.add_singleton::<TestDisposableSecond>();
+    let root_provider = service_container.build();
+    let log = get_instance::<TestDisposeLog>(&root_provider).expect("Cannot get service");
+
+    get_instance::<TestDisposableSecond>(&root_provider).expect("Cannot get service");
+    root_provider.create_scope().dispose();
+    assert!(log.names().is_empty());
+
+    root_provider.shutdown();
+    assert_eq!(log.names(), vec!["second", "first"]);
+
+    // Disposed singletons are created again after a shutdown
+    get_instance::<TestDisposableFirst>(&root_provider).expect("Cannot get service");
+    drop(root_provider);
+    assert_eq!(log.names(), vec!["second", "first", "first"]);
+}